@@ -0,0 +1,611 @@
+use crate::{inline, Diagnostic};
+use itertools::Itertools;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    mem,
+};
+use toml::Value;
+
+/// Target of the `[target.<target>]` table: a target triple or a `cfg(...)`
+/// expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Target {
+    Triple(String),
+    Cfg(Cfg),
+}
+
+impl Target {
+    /// Parses the target. Returns `None` if the `cfg(...)` expression is
+    /// malformed.
+    pub(crate) fn parse(target: &str) -> Option<Self> {
+        match target.trim().strip_prefix("cfg") {
+            Some(rest) if rest.trim_start().starts_with('(') => {
+                let mut parser = Parser { rest };
+                parser.eat('(')?;
+                let cfg = parser.predicate()?;
+                parser.eat(')')?;
+                if parser.rest.trim().is_empty() {
+                    Some(Self::Cfg(cfg))
+                } else {
+                    None
+                }
+            }
+            _ => Some(Self::Triple(target.to_string())),
+        }
+    }
+
+    /// Canonicalizes the `cfg(...)` expression, so the semantically identical
+    /// expressions are equal. The whitespace is canonicalized by display.
+    pub(crate) fn canonicalize(&mut self) {
+        if let Self::Cfg(cfg) = self {
+            cfg.canonicalize();
+        }
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Triple(triple) => write!(f, "{}", triple),
+            Self::Cfg(cfg) => write!(f, "cfg({})", cfg),
+        }
+    }
+}
+
+/// `cfg` predicate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Cfg {
+    Name(String),
+    KeyValue(String, String),
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
+
+impl Cfg {
+    // `target_family = "unix"` is `unix`, `not(not(x))` is `x`, nested
+    // `all(...)` (`any(...)`) predicates are flattened, their predicates are
+    // sorted and deduplicated, and `all(x)` (`any(x)`) is `x`.
+    fn canonicalize(&mut self) {
+        match self {
+            Self::Name(_) => {}
+            Self::KeyValue(key, value) => {
+                if key == "target_family" && FAMILIES.contains(&value.as_str()) {
+                    *self = Self::Name(value.clone());
+                }
+            }
+            Self::All(predicates) => *self = Self::combine(mem::take(predicates), true),
+            Self::Any(predicates) => *self = Self::combine(mem::take(predicates), false),
+            Self::Not(predicate) => {
+                predicate.canonicalize();
+                if let Self::Not(nested) = &mut **predicate {
+                    *self = mem::replace(&mut **nested, Self::All(Vec::new()));
+                }
+            }
+        }
+    }
+
+    // Canonical `all(...)` (or `any(...)`) of the predicates.
+    fn combine(predicates: Vec<Self>, all: bool) -> Self {
+        let mut flattened = Vec::new();
+        for mut predicate in predicates {
+            predicate.canonicalize();
+            match predicate {
+                Self::All(nested) if all => flattened.extend(nested),
+                Self::Any(nested) if !all => flattened.extend(nested),
+                predicate => flattened.push(predicate),
+            }
+        }
+        flattened.sort_by_cached_key(ToString::to_string);
+        flattened.dedup();
+        match (flattened.len(), all) {
+            (1, _) => flattened.remove(0),
+            (_, true) => Self::All(flattened),
+            (_, false) => Self::Any(flattened),
+        }
+    }
+
+    // Family (`unix` or `windows`) implied by the predicate: by the family
+    // itself or by the `target_os` of the family. `all(...)` implies the
+    // family of any of its predicates, `any(...)` the family of all of them.
+    fn family(&self) -> Option<&'static str> {
+        fn family(name: &str) -> Option<&'static str> {
+            FAMILIES.iter().copied().find(|family| *family == name)
+        }
+
+        match self {
+            Self::Name(name) => family(name),
+            Self::KeyValue(key, value) => match key.as_str() {
+                "target_family" => family(value),
+                "target_os" if value == "windows" => Some("windows"),
+                "target_os" if UNIX.contains(&value.as_str()) => Some("unix"),
+                _ => None,
+            },
+            Self::All(predicates) => predicates.iter().find_map(Self::family),
+            Self::Any(predicates) => {
+                let mut families = predicates.iter().map(Self::family);
+                let first = families.next()??;
+                if families.all(|family| family == Some(first)) {
+                    Some(first)
+                } else {
+                    None
+                }
+            }
+            Self::Not(_) => None,
+        }
+    }
+}
+
+// Target families with a `cfg` name.
+const FAMILIES: &[&str] = &["unix", "windows"];
+
+// `target_os` values of the unix family.
+const UNIX: &[&str] = &[
+    "android",
+    "dragonfly",
+    "freebsd",
+    "haiku",
+    "illumos",
+    "ios",
+    "linux",
+    "macos",
+    "netbsd",
+    "openbsd",
+    "redox",
+    "solaris",
+];
+
+impl Display for Cfg {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{}", name),
+            Self::KeyValue(key, value) => write!(f, "{} = \"{}\"", key, value),
+            Self::All(predicates) => write!(f, "all({})", predicates.iter().format(", ")),
+            Self::Any(predicates) => write!(f, "any({})", predicates.iter().format(", ")),
+            Self::Not(predicate) => write!(f, "not({})", predicate),
+        }
+    }
+}
+
+// `cfg` expression parser.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self, token: char) -> bool {
+        self.rest.trim_start().starts_with(token)
+    }
+
+    fn eat(&mut self, token: char) -> Option<()> {
+        self.rest = self.rest.trim_start().strip_prefix(token)?;
+        Some(())
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        self.rest = &rest[end..];
+        Some(&rest[..end])
+    }
+
+    fn string(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start().strip_prefix('"')?;
+        let end = rest.find('"')?;
+        self.rest = &rest[end + 1..];
+        Some(&rest[..end])
+    }
+
+    fn predicate(&mut self) -> Option<Cfg> {
+        let identifier = self.identifier()?;
+        match identifier {
+            "all" | "any" | "not" if self.peek('(') => {
+                self.eat('(')?;
+                let mut predicates = Vec::new();
+                while !self.peek(')') {
+                    predicates.push(self.predicate()?);
+                    if !self.peek(')') {
+                        self.eat(',')?;
+                    }
+                }
+                self.eat(')')?;
+                match identifier {
+                    "all" => Some(Cfg::All(predicates)),
+                    "any" => Some(Cfg::Any(predicates)),
+                    _ if predicates.len() == 1 => Some(Cfg::Not(Box::new(predicates.pop()?))),
+                    _ => None,
+                }
+            }
+            _ if self.peek('=') => {
+                self.eat('=')?;
+                let value = self.string()?;
+                Some(Cfg::KeyValue(identifier.to_string(), value.to_string()))
+            }
+            _ => Some(Cfg::Name(identifier.to_string())),
+        }
+    }
+}
+
+/// Canonical form of the target (as is if the target is malformed).
+pub(crate) fn canonical(target: &str) -> String {
+    match Target::parse(target) {
+        Some(mut target) => {
+            target.canonicalize();
+            target.to_string()
+        }
+        None => target.to_string(),
+    }
+}
+
+/// Semantic sort key of the target: target triples first, then `cfg(...)`
+/// expressions grouped by family, then the other `cfg(...)` expressions, then
+/// malformed targets.
+pub(crate) fn key(target: &str) -> (usize, &'static str, String) {
+    match Target::parse(target) {
+        Some(Target::Triple(triple)) => (0, "", triple),
+        Some(Target::Cfg(mut cfg)) => {
+            cfg.canonicalize();
+            match cfg.family() {
+                Some(family) => (1, family, cfg.to_string()),
+                None => (2, "", cfg.to_string()),
+            }
+        }
+        None => (3, "", target.to_string()),
+    }
+}
+
+/// Keys of the targets (`targets.canonicalize`): the canonical form of each
+/// target, unless it's shared by several targets (they keep their keys).
+pub(crate) fn canonical_keys(targets: &[&str]) -> Vec<String> {
+    let mut counts = HashMap::new();
+    for target in targets {
+        *counts.entry(canonical(target)).or_insert(0) += 1;
+    }
+    targets
+        .iter()
+        .map(|target| {
+            let canonical = canonical(target);
+            if counts[&canonical] == 1 {
+                canonical
+            } else {
+                target.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Value of the target tables merged by `targets.merge` (the parsed manifest
+/// item for the formatting, `toml::Value` for the verification).
+pub(crate) trait Merge: Sized {
+    /// Whether the value is a table.
+    fn is_table(&self) -> bool;
+
+    /// Entries of the table (the value itself if it isn't a table).
+    fn entries(self) -> Result<Vec<(String, Self)>, Self>;
+
+    /// Value of the entry of the table.
+    fn entry(&mut self, key: &str) -> Option<&mut Self>;
+
+    /// Inserts the entry into the table.
+    fn insert(&mut self, key: String, value: Self);
+
+    /// Whether the values are the same.
+    fn same(&self, other: &Self) -> bool;
+}
+
+impl Merge for Value {
+    fn is_table(&self) -> bool {
+        matches!(self, Value::Table(_))
+    }
+
+    fn entries(self) -> Result<Vec<(String, Self)>, Self> {
+        match self {
+            Value::Table(table) => Ok(table.into_iter().collect()),
+            value => Err(value),
+        }
+    }
+
+    fn entry(&mut self, key: &str) -> Option<&mut Self> {
+        self.get_mut(key)
+    }
+
+    fn insert(&mut self, key: String, value: Self) {
+        if let Value::Table(table) = self {
+            table.insert(key, value);
+        }
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+/// Merges the tables of the semantically identical targets into the table of
+/// the first one (`targets.merge`): the dependency tables are merged, the same
+/// dependency has to have the same spec. Returns the merged targets and the
+/// keys (the target, the dependency table and the dependency) of the
+/// conflicting entries. A conflicting entry keeps the value of the first
+/// target.
+pub(crate) fn merge<T: Merge>(targets: Vec<(String, T)>) -> (Vec<(String, T)>, Vec<Vec<String>>) {
+    let mut merged: Vec<(String, T)> = Vec::new();
+    let mut indices = HashMap::new();
+    let mut conflicts = Vec::new();
+    for (target, value) in targets {
+        let canonical = canonical(&target);
+        match indices.get(&canonical) {
+            Some(&index) => {
+                let (_, base) = &mut merged[index];
+                merge_entries(base, value, 2, &mut vec![target], &mut conflicts);
+            }
+            None => {
+                indices.insert(canonical, merged.len());
+                merged.push((target, value));
+            }
+        }
+    }
+    (merged, conflicts)
+}
+
+// Merges the value into the base one: the tables are merged down to the
+// depth, any other value has to be the same.
+fn merge_entries<T: Merge>(
+    base: &mut T,
+    value: T,
+    depth: usize,
+    keys: &mut Vec<String>,
+    conflicts: &mut Vec<Vec<String>>,
+) {
+    let value = if depth > 0 && base.is_table() {
+        match value.entries() {
+            Ok(entries) => {
+                for (key, value) in entries {
+                    match base.entry(&key) {
+                        Some(base) => {
+                            keys.push(key);
+                            merge_entries(base, value, depth - 1, keys, conflicts);
+                            keys.pop();
+                        }
+                        None => base.insert(key, value),
+                    }
+                }
+                return;
+            }
+            Err(value) => value,
+        }
+    } else {
+        value
+    };
+    if !base.same(&value) {
+        conflicts.push(keys.clone());
+    }
+}
+
+/// Path of the entry of the `[target]` table with the keys.
+pub(crate) fn path(keys: &[String]) -> String {
+    let keys = keys.iter().map(|key| inline::key(key));
+    format!("target.{}", keys.format("."))
+}
+
+/// Warns about the semantically identical targets of the manifest (e.g.
+/// `cfg(unix)` and `cfg( unix )`). Their tables should be merged.
+pub fn duplicate_targets(manifest: &Value) -> Vec<Diagnostic> {
+    let mut canonicals = HashMap::new();
+    let mut diagnostics = Vec::new();
+    let targets = manifest.get("target").and_then(Value::as_table);
+    for target in targets.into_iter().flat_map(|targets| targets.keys()) {
+        let canonical = canonical(target);
+        match canonicals.get(&canonical) {
+            Some(first) => diagnostics.push(
+                Diagnostic::warning(format!("target `{}` is the same as `{}`", target, first))
+                    .path(format!("target.{}", inline::key(target)))
+                    .note("merge the tables of the targets"),
+            ),
+            None => {
+                canonicals.insert(canonical, target);
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Target::parse("x86_64-unknown-linux-gnu"),
+            Some(Target::Triple("x86_64-unknown-linux-gnu".to_string())),
+        );
+        assert_eq!(
+            Target::parse(r#"cfg( all( unix , not(target_os="macos") ) )"#),
+            Some(Target::Cfg(Cfg::All(vec![
+                Cfg::Name("unix".to_string()),
+                Cfg::Not(Box::new(Cfg::KeyValue(
+                    "target_os".to_string(),
+                    "macos".to_string(),
+                ))),
+            ]))),
+        );
+        assert_eq!(Target::parse("cfg(not(unix, windows))"), None);
+        assert_eq!(Target::parse("cfg(unix"), None);
+    }
+
+    #[test]
+    fn canonical() {
+        assert_eq!(
+            super::canonical(r#"cfg(all(not(target_os="macos"),unix))"#),
+            r#"cfg(all(not(target_os = "macos"), unix))"#,
+        );
+        assert_eq!(super::canonical("cfg( unix )"), "cfg(unix)");
+        assert_eq!(super::canonical("cfg(unix"), "cfg(unix");
+        assert_eq!(
+            super::canonical(r#"cfg(target_family = "unix")"#),
+            "cfg(unix)"
+        );
+        assert_eq!(super::canonical("cfg(all(unix))"), "cfg(unix)");
+        assert_eq!(super::canonical("cfg(any(unix, unix))"), "cfg(unix)");
+        assert_eq!(super::canonical("cfg(not(not(unix)))"), "cfg(unix)");
+        assert_eq!(
+            super::canonical("cfg(all(windows, all(test, any(unix))))"),
+            "cfg(all(test, unix, windows))",
+        );
+        assert_eq!(
+            super::canonical("cfg(any(all(unix, test), windows))"),
+            "cfg(any(all(test, unix), windows))",
+        );
+    }
+
+    #[test]
+    fn family() {
+        let family = |target| match Target::parse(target) {
+            Some(Target::Cfg(cfg)) => cfg.family(),
+            _ => None,
+        };
+        assert_eq!(family("cfg(unix)"), Some("unix"));
+        assert_eq!(family(r#"cfg(target_family = "windows")"#), Some("windows"));
+        assert_eq!(family(r#"cfg(target_os = "linux")"#), Some("unix"));
+        assert_eq!(family(r#"cfg(target_os = "windows")"#), Some("windows"));
+        assert_eq!(
+            family(r#"cfg(all(target_arch = "x86", windows))"#),
+            Some("windows")
+        );
+        assert_eq!(
+            family(r#"cfg(any(target_os = "macos", unix))"#),
+            Some("unix")
+        );
+        assert_eq!(family("cfg(any(unix, windows))"), None);
+        assert_eq!(family("cfg(not(unix))"), None);
+        assert_eq!(family(r#"cfg(target_arch = "x86")"#), None);
+    }
+
+    #[test]
+    fn key() {
+        let mut targets = vec![
+            r#"cfg(target_os = "linux")"#,
+            "cfg(windows)",
+            "cfg(not(unix))",
+            "x86_64-pc-windows-msvc",
+            r#"cfg(all(target_arch = "x86", windows))"#,
+            r#"cfg(target_family = "unix")"#,
+        ];
+        targets.sort_by_key(|target| super::key(target));
+        assert_eq!(
+            targets,
+            vec![
+                "x86_64-pc-windows-msvc",
+                r#"cfg(target_os = "linux")"#,
+                r#"cfg(target_family = "unix")"#,
+                r#"cfg(all(target_arch = "x86", windows))"#,
+                "cfg(windows)",
+                "cfg(not(unix))",
+            ],
+        );
+    }
+
+    #[test]
+    fn canonical_keys() {
+        let targets = [
+            "cfg( unix )",
+            "cfg(all(windows))",
+            "cfg(target_family = \"windows\")",
+        ];
+        assert_eq!(
+            super::canonical_keys(&targets),
+            [
+                "cfg(unix)",
+                "cfg(all(windows))",
+                "cfg(target_family = \"windows\")"
+            ],
+        );
+    }
+
+    #[test]
+    fn merge() -> Result<()> {
+        const SOURCE: &str = r#"
+            [target.'cfg(unix)'.dependencies]
+            a = "1.0.0"
+            b = { version = "1.0.0", optional = true }
+
+            [target."cfg( unix )".dependencies]
+            a = '1.0.0'
+            b = { version = "1.0.0" }
+            c = "1.0.0"
+
+            [target."cfg( unix )".dev-dependencies]
+            d = "1.0.0"
+        "#;
+        const TARGET: &str = r#"
+            [target.'cfg(unix)'.dependencies]
+            a = "1.0.0"
+            b = { version = "1.0.0", optional = true }
+            c = "1.0.0"
+
+            [target.'cfg(unix)'.dev-dependencies]
+            d = "1.0.0"
+        "#;
+        let targets = |source: &str| -> Result<Vec<(String, Value)>> {
+            let mut manifest: Value = toml::from_str(source)?;
+            Ok(manifest["target"]
+                .as_table_mut()
+                .map(mem::take)
+                .unwrap_or_default()
+                .into_iter()
+                .collect())
+        };
+        let (merged, conflicts) = super::merge(targets(SOURCE)?);
+        assert_eq!(merged, targets(TARGET)?);
+        let keys = ["cfg( unix )", "dependencies", "b"];
+        assert_eq!(
+            conflicts,
+            [keys.iter().map(ToString::to_string).collect::<Vec<_>>()]
+        );
+        assert_eq!(path(&conflicts[0]), "target.\"cfg( unix )\".dependencies.b");
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_targets() -> Result<()> {
+        const SOURCE: &str = r#"
+            [target.'cfg(unix)'.dependencies]
+            a = "1.0.0"
+
+            [target."cfg( unix )".dependencies]
+            b = "1.0.0"
+
+            [target.'cfg(target_family = "unix")'.dependencies]
+            c = "1.0.0"
+
+            [target.'cfg(all(unix))'.dependencies]
+            d = "1.0.0"
+        "#;
+        let warning = |target: &str, path: &str| {
+            Diagnostic::warning(format!("target `{}` is the same as `cfg(unix)`", target))
+                .path(path)
+                .note("merge the tables of the targets")
+        };
+        assert_eq!(
+            super::duplicate_targets(&toml::from_str(SOURCE)?),
+            vec![
+                warning("cfg( unix )", "target.\"cfg( unix )\""),
+                warning(
+                    r#"cfg(target_family = "unix")"#,
+                    r#"target."cfg(target_family = \"unix\")""#,
+                ),
+                warning("cfg(all(unix))", "target.\"cfg(all(unix))\""),
+            ],
+        );
+        Ok(())
+    }
+}
@@ -1,46 +1,341 @@
-use crate::options::Pretty;
-use anyhow::Result;
-use cargo_pretty::{Format as _, Settings};
+use crate::{diff::diff, options::Pretty};
+use anyhow::{anyhow, bail, Context, Result};
+use cargo_pretty::{
+    duplicate_targets, feature_cycles, merge_targets, settings::merge, Diagnostic, Format as _,
+    Order, Ordered, Settings,
+};
+use glob::{glob, Pattern};
+use itertools::Itertools;
 use log::{debug, info, trace};
 use std::{
     borrow::Cow,
+    collections::{HashMap, HashSet},
     fs::{read_to_string, rename, write},
-    path::Path,
+    io::{stdin, stdout, Read},
+    path::{Path, PathBuf},
+    rc::Rc,
 };
+use toml::Value;
 use toml_lalrpop::TomlParser;
 
-pub(crate) fn pretty(pretty: &Pretty) -> Result<()> {
+/// Exit status.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Status {
+    Success,
+    Unformatted,
+    Error,
+}
+
+impl Status {
+    pub(crate) fn code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::Error => 1,
+            Self::Unformatted => 2,
+        }
+    }
+}
+
+pub(crate) fn pretty(pretty: &Pretty) -> Result<Status> {
+    trace!("stdin: {:?}", pretty.stdin);
+    if pretty.stdin {
+        return self::stdin(pretty);
+    }
+    let manifest_files = manifest_files(pretty)?;
+    Ok(each(
+        &manifest_files,
+        pretty.config_path.as_deref(),
+        |settings, manifest_file| manifest(pretty, settings, manifest_file),
+    ))
+}
+
+// Runs the command for each manifest file with the settings for it (from the
+// config path or the directory of the file). The error for a file is reported
+// and the next file is processed. Returns the highest status.
+fn each(
+    manifest_files: &[PathBuf],
+    config_path: Option<&Path>,
+    mut command: impl FnMut(&Settings, &Path) -> Result<Status>,
+) -> Status {
+    let mut resolver = Resolver::default();
+    let mut status = Status::Success;
+    for manifest_file in manifest_files {
+        debug!("manifest_file: {}", manifest_file.display());
+        let config_path = config_path.unwrap_or_else(|| dir(manifest_file));
+        let manifest_status = resolver
+            .resolve(config_path)
+            .and_then(|settings| command(&settings, manifest_file))
+            .unwrap_or_else(|err| {
+                let diagnostic = Diagnostic::error(format!("{:#}", err));
+                eprint!("{}", diagnostic.display(manifest_file, ""));
+                Status::Error
+            });
+        status = status.max(manifest_status);
+    }
+    status
+}
+
+fn manifest(pretty: &Pretty, settings: &Settings, manifest_file: &Path) -> Result<Status> {
     use super::options::Output;
 
-    let settings = settings(&pretty.config_path)?;
+    let source = read_to_string(manifest_file)?;
+    let target = match format(&source, settings, !pretty.no_verify) {
+        Ok(target) => target,
+        Err(diagnostic) => {
+            eprint!("{}", diagnostic.display(manifest_file, &source));
+            return Ok(Status::Error);
+        }
+    };
+    for warning in warnings(&source, settings) {
+        eprint!("{}", warning.display(manifest_file, &source));
+    }
+    let target = target.as_str();
+    trace!("check: {:?}", pretty.check);
+    if pretty.check {
+        if source != target {
+            info!("manifest file isn't formated: {}", manifest_file.display());
+            println!("{}", manifest_file.display());
+            return Ok(Status::Unformatted);
+        }
+        return Ok(Status::Success);
+    }
+    trace!("output: {:?}", pretty.output);
+    match pretty.output {
+        Output::Diff => {
+            diff(
+                stdout(),
+                manifest_file,
+                &source,
+                target,
+                pretty.color.enabled(),
+            )?;
+        }
+        Output::Stdout => {
+            print!("{}", target);
+        }
+        Output::File => {
+            if source != target {
+                trace!("backup: {:?}", pretty.backup);
+                if pretty.backup {
+                    let backup_file = manifest_file.with_extension("toml.bk");
+                    rename(manifest_file, backup_file)?;
+                }
+                info!("manifest file was formated: {}", manifest_file.display());
+                write(manifest_file, target)?;
+            }
+        }
+    }
+    Ok(Status::Success)
+}
+
+fn stdin(pretty: &Pretty) -> Result<Status> {
+    trace!("stdin_filepath: {:?}", pretty.stdin_filepath);
+    let config_path = match (&pretty.config_path, &pretty.stdin_filepath) {
+        (Some(config_path), _) => config_path.as_path(),
+        (None, Some(file)) => dir(file),
+        (None, None) => Path::new("./"),
+    };
+    let settings = settings(config_path)?;
     trace!("settings: {:?}", settings);
-    for manifest_file in &pretty.files {
-        debug!("manifest_file: {}", manifest_file.display());
-        let source = read_to_string(manifest_file)?;
-        let mut manifest = TomlParser::new()
-            .parse(&source)
-            .map_err(|err| err.map_token(|token| token.to_string()))?;
-        let target = manifest.format(&settings).to_string();
-        let target = target.trim();
-        trace!("output: {:?}", pretty.output);
-        match pretty.output {
-            Output::Stdout => {
-                println!("{}", target);
+    let mut source = String::new();
+    stdin().read_to_string(&mut source)?;
+    let file = pretty
+        .stdin_filepath
+        .as_deref()
+        .unwrap_or_else(|| Path::new("<stdin>"));
+    match format(&source, &settings, !pretty.no_verify) {
+        Ok(target) => {
+            for warning in warnings(&source, &settings) {
+                eprint!("{}", warning.display(file, &source));
             }
-            Output::File => {
-                if source != target {
-                    trace!("backup: {:?}", pretty.backup);
-                    if pretty.backup {
-                        let backup_file = manifest_file.with_extension("toml.bk");
-                        rename(manifest_file, backup_file)?;
-                    }
-                    info!("manifest file was formated: {}", manifest_file.display());
-                    write(manifest_file, target)?;
-                }
+            print!("{}", target);
+            Ok(Status::Success)
+        }
+        Err(diagnostic) => {
+            eprint!("{}", diagnostic.display(file, &source));
+            Ok(Status::Error)
+        }
+    }
+}
+
+fn format(source: &str, settings: &Settings, verify: bool) -> Result<String, Diagnostic> {
+    let mut manifest = TomlParser::new().parse(source)?;
+    merge_targets(&mut manifest, settings)?;
+    // The formatted manifest ends with a single newline.
+    let target = format!("{}\n", manifest.format(settings).to_string().trim());
+    trace!("verify: {:?}", verify);
+    if verify {
+        let differences = cargo_pretty::verify(source, &target, settings)
+            .map_err(|err| Diagnostic::error(format!("can't verify the manifest: {}", err)))?;
+        if !differences.is_empty() {
+            let diagnostic = Diagnostic::error("formatting changed the meaning of the manifest");
+            return Err(differences
+                .iter()
+                .fold(diagnostic, |diagnostic, difference| {
+                    diagnostic.note(difference.to_string())
+                }));
+        }
+    }
+    Ok(target)
+}
+
+// Fixes the trivial lint findings in the manifest source. The source has to
+// parse and the fixed manifest has to differ only by the trailing periods of
+// the fixed descriptions.
+fn fix(source: &str, settings: &Settings) -> Result<String, Diagnostic> {
+    TomlParser::new().parse(source)?;
+    if !settings.package.description.no_trailing_period {
+        return Ok(source.to_string());
+    }
+    let (fixed, paths) = cargo_pretty::fix(source, settings);
+    let error = |err| Diagnostic::error(format!("can't verify the fixed manifest: {}", err));
+    let mut expected: Value = toml::from_str(source).map_err(error)?;
+    for path in &paths {
+        let description = path
+            .iter()
+            .try_fold(&mut expected, |value, key| value.get_mut(key));
+        if let Some(Value::String(description)) = description {
+            description.pop();
+        }
+    }
+    if toml::from_str::<Value>(&fixed).map_err(error)? != expected {
+        return Err(Diagnostic::error(
+            "fixing changed the meaning of the manifest",
+        ));
+    }
+    Ok(fixed)
+}
+
+// Warnings for the manifest.
+fn warnings(source: &str, settings: &Settings) -> Vec<Diagnostic> {
+    let manifest = match toml::from_str(source) {
+        Ok(manifest) => manifest,
+        Err(_) => return Vec::new(),
+    };
+    let mut warnings = Vec::new();
+    if settings.targets.canonicalize && !settings.targets.merge {
+        warnings.extend(duplicate_targets(&manifest));
+    }
+    if settings.features.order == Order::Ordered(Ordered::Topological) {
+        warnings.extend(feature_cycles(&manifest));
+    }
+    warnings
+        .into_iter()
+        .map(|warning| locate(warning, source))
+        .collect()
+}
+
+// Points the diagnostic to the source of its path (if found).
+fn locate(diagnostic: Diagnostic, source: &str) -> Diagnostic {
+    let span = match (&diagnostic.span, &diagnostic.path) {
+        (None, Some(path)) => cargo_pretty::span(source, path),
+        _ => None,
+    };
+    match span {
+        Some(span) => diagnostic.span(span),
+        None => diagnostic,
+    }
+}
+
+fn manifest_files(pretty: &Pretty) -> Result<Vec<PathBuf>> {
+    trace!("workspace: {:?}", pretty.workspace);
+    if !pretty.workspace {
+        return Ok(pretty.files.clone());
+    }
+    let mut canonical_files = HashSet::new();
+    let mut files = Vec::new();
+    for root_file in &pretty.files {
+        for file in workspace(root_file)? {
+            if canonical_files.insert(file.canonicalize()?) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+// Root manifest file and member manifest files of the workspace.
+fn workspace(root_file: &Path) -> Result<Vec<PathBuf>> {
+    let manifest: Value = toml::from_str(&read_to_string(root_file)?)?;
+    let workspace = manifest
+        .get("workspace")
+        .ok_or_else(|| anyhow!("isn't workspace root: {}", root_file.display()))?;
+    let root = root_file.parent().unwrap_or_else(|| Path::new(""));
+    let patterns = |key| {
+        workspace
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|pattern| root.join(pattern))
+    };
+    let exclude = patterns("exclude")
+        .map(|path| Ok((Pattern::new(&path.to_string_lossy())?, path)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut files = vec![root_file.to_path_buf()];
+    for pattern in patterns("members") {
+        for member in glob(&pattern.to_string_lossy())? {
+            let member = member?;
+            if exclude
+                .iter()
+                .any(|(pattern, path)| pattern.matches_path(&member) || member.starts_with(path))
+            {
+                debug!("excluded member: {}", member.display());
+                continue;
             }
+            let file = member.join("Cargo.toml");
+            if file.is_file() {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+// Directory of the file.
+fn dir(file: &Path) -> &Path {
+    match file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("./"),
+    }
+}
+
+/// Settings resolver.
+///
+/// Caches the settings resolved for each directory and read from each config
+/// file.
+#[derive(Default)]
+struct Resolver {
+    dirs: HashMap<PathBuf, Rc<Settings>>,
+    config_files: HashMap<PathBuf, Rc<Settings>>,
+}
+
+impl Resolver {
+    fn resolve(&mut self, path: &Path) -> Result<Rc<Settings>> {
+        let dir = path.canonicalize()?;
+        if let Some(settings) = self.dirs.get(&dir) {
+            return Ok(Rc::clone(settings));
         }
+        let settings = match config(&dir)? {
+            Some(config_file) => match self.config_files.get(config_file.as_ref()) {
+                Some(settings) => Rc::clone(settings),
+                None => {
+                    let settings = Rc::new(read_settings(&config_file)?);
+                    trace!("settings: {:?}", settings);
+                    self.config_files
+                        .insert(config_file.into_owned(), Rc::clone(&settings));
+                    settings
+                }
+            },
+            None => {
+                info!("default settings are used for {}", dir.display());
+                Rc::new(Settings::default())
+            }
+        };
+        self.dirs.insert(dir, Rc::clone(&settings));
+        Ok(settings)
     }
-    Ok(())
 }
 
 fn config(path: &Path) -> Result<Option<Cow<Path>>> {
@@ -64,11 +359,7 @@ fn config(path: &Path) -> Result<Option<Cow<Path>>> {
 
 fn settings(path: &Path) -> Result<Settings> {
     match config(path)? {
-        Some(ref path) => {
-            let content = read_to_string(path)?;
-            info!("{} settings are used", path.display());
-            Ok(toml::from_str(&content)?)
-        }
+        Some(ref path) => read_settings(path),
         _ => {
             info!("default settings are used");
             Ok(Settings::default())
@@ -76,19 +367,81 @@ fn settings(path: &Path) -> Result<Settings> {
     }
 }
 
+fn read_settings(config_file: &Path) -> Result<Settings> {
+    let value = read_config(config_file, &mut Vec::new())?;
+    info!("{} settings are used", config_file.display());
+    Ok(value.try_into()?)
+}
+
+// Config merged with the configs it extends.
+fn read_config(config_file: &Path, extended: &mut Vec<PathBuf>) -> Result<Value> {
+    // The config file and the config file extending it (if any).
+    let file = || match extended.last() {
+        Some(extending) => format!(
+            "{} (extended by {})",
+            config_file.display(),
+            extending.display()
+        ),
+        None => config_file.display().to_string(),
+    };
+    let config_file = config_file
+        .canonicalize()
+        .with_context(|| format!("can't find the config file {}", file()))?;
+    if extended.contains(&config_file) {
+        bail!(
+            "config files extend each other: {} -> {}",
+            extended.iter().map(|file| file.display()).format(" -> "),
+            config_file.display(),
+        );
+    }
+    let source = read_to_string(&config_file)
+        .with_context(|| format!("can't read the config file {}", file()))?;
+    let mut config: Value = toml::from_str(&source)
+        .with_context(|| format!("can't parse the config file {}", file()))?;
+    let dir = dir(&config_file);
+    let parent_file = match config.get("extends").and_then(Value::as_str) {
+        Some(extends) => Some(dir.join(extends)),
+        None if config.get("inherit").and_then(Value::as_bool) == Some(true) => {
+            match dir.parent() {
+                Some(parent) => self::config(parent)?.map(Cow::into_owned),
+                None => None,
+            }
+        }
+        None => None,
+    };
+    if let Some(parent_file) = parent_file {
+        debug!(
+            "{} extends {}",
+            config_file.display(),
+            parent_file.display()
+        );
+        extended.push(config_file.clone());
+        let mut parent = read_config(&parent_file, extended)?;
+        if let Some(parent) = parent.as_table_mut() {
+            parent.remove("extends");
+            parent.remove("inherit");
+        }
+        merge(&mut parent, config);
+        config = parent;
+    }
+    Ok(config)
+}
+
 pub(crate) mod pretty {
-    use super::settings;
-    use crate::options::config::Config;
+    use super::{each, fix, locate, settings, Status};
+    use crate::options::{config::Config, lint::Lint};
     use anyhow::Result;
-    use cargo_pretty::Settings;
-    use log::trace;
+    use cargo_pretty::{Diagnostic, Level, Settings};
+    use log::{info, trace};
     use ron::ser::PrettyConfig;
     use serde::Serialize;
     use serde_diff::Diff;
     use std::{
-        fs::File,
+        fs::{read_to_string, rename, write, File},
         io::{stdout, Write},
+        path::Path,
     };
+    use toml_lalrpop::TomlParser;
 
     pub(crate) fn config(config: &Config) -> Result<()> {
         use crate::options::config::{Format, Output, Type};
@@ -117,6 +470,54 @@ pub(crate) mod pretty {
         Ok(())
     }
 
+    pub(crate) fn lint(lint: &Lint) -> Result<Status> {
+        Ok(each(
+            &lint.files,
+            lint.config_path.as_deref(),
+            |settings, manifest_file| manifest(lint, settings, manifest_file),
+        ))
+    }
+
+    fn manifest(lint: &Lint, settings: &Settings, manifest_file: &Path) -> Result<Status> {
+        let mut source = read_to_string(manifest_file)?;
+        trace!("fix: {:?}", lint.fix);
+        if lint.fix {
+            match fix(&source, settings) {
+                Ok(fixed) if fixed != source => {
+                    trace!("backup: {:?}", lint.backup);
+                    if lint.backup {
+                        let backup_file = manifest_file.with_extension("toml.bk");
+                        rename(manifest_file, backup_file)?;
+                    }
+                    info!("manifest file was fixed: {}", manifest_file.display());
+                    write(manifest_file, &fixed)?;
+                    source = fixed;
+                }
+                Ok(_) => {}
+                Err(diagnostic) => {
+                    eprint!("{}", diagnostic.display(manifest_file, &source));
+                    return Ok(Status::Error);
+                }
+            }
+        }
+        let diagnostics = match TomlParser::new().parse(&source) {
+            Ok(_) => match toml::from_str(&source) {
+                Ok(manifest) => cargo_pretty::lint(&manifest, settings),
+                Err(err) => vec![Diagnostic::error(format!("{:#}", err))],
+            },
+            Err(err) => vec![err.into()],
+        };
+        let mut status = Status::Success;
+        for diagnostic in diagnostics {
+            let diagnostic = locate(diagnostic, &source);
+            eprint!("{}", diagnostic.display(manifest_file, &source));
+            if diagnostic.level == Level::Error {
+                status = Status::Error;
+            }
+        }
+        Ok(status)
+    }
+
     fn json<W: Write, T: Serialize>(writer: W, serialize: T) -> Result<()> {
         let mut serializer = serde_json::Serializer::pretty(writer);
         serialize.serialize(&mut serializer)?;
@@ -138,3 +539,242 @@ pub(crate) mod pretty {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cargo_pretty::Inline;
+    use std::{
+        env,
+        fs::{create_dir_all, remove_dir_all},
+        process,
+    };
+
+    #[test]
+    fn newline() {
+        let settings = Settings::default();
+        const TARGET: &str = "[package]\nname = \"a\"\n";
+        for source in &[
+            "[package]\nname = \"a\"",
+            TARGET,
+            "\n[package]\nname = \"a\"\n\n\n",
+        ] {
+            assert_eq!(format(source, &settings, true), Ok(TARGET.to_string()));
+        }
+    }
+
+    #[test]
+    fn verify_sorted() {
+        const SOURCE: &str = "\
+            [package]\n\
+            name = \"a\"\n\
+            keywords = [\"b\", \"a\"]\n\
+            \n\
+            [features]\n\
+            std = [\"serde/std\", \"alloc\"]\n\
+        ";
+        const TARGET: &str = "\
+            [package]\n\
+            name = \"a\"\n\
+            keywords = [\"a\", \"b\"]\n\
+            \n\
+            [features]\n\
+            std = [\"alloc\", \"serde/std\"]\n\
+        ";
+        let settings = Settings::default();
+        assert_eq!(format(SOURCE, &settings, true), Ok(TARGET.to_string()));
+    }
+
+    #[test]
+    fn fix() {
+        const SOURCE: &str = "\
+            [package]\n\
+            name = \"a\"\n\
+            description = \"Formats the manifest.\"\n\
+            \n\
+            [package.metadata]\n\
+            description = \"Formats the manifest.\"\n\
+        ";
+        const TARGET: &str = "\
+            [package]\n\
+            name = \"a\"\n\
+            description = \"Formats the manifest\"\n\
+            \n\
+            [package.metadata]\n\
+            description = \"Formats the manifest.\"\n\
+        ";
+        let settings = Settings::default();
+        assert_eq!(super::fix(SOURCE, &settings), Ok(TARGET.to_string()));
+        assert_eq!(super::fix(TARGET, &settings), Ok(TARGET.to_string()));
+        assert!(super::fix("[package\ndescription = \"a.\"\n", &settings).is_err());
+    }
+
+    #[test]
+    fn fix_unchanged() {
+        const MULTILINE: &str = "\
+            [package]\n\
+            name = \"a\"\n\
+            description = \"\"\"\n\
+            Formats the manifest.\"\"\"\n\
+        ";
+        const INLINE: &str =
+            "package = { name = \"a\", description = \"Formats the manifest.\" }\n";
+        const SOURCE: &str = "\
+            [package]\n\
+            name = \"a\"\n\
+            description = \"Formats the manifest.\"\n\
+        ";
+        let mut settings = Settings::default();
+        assert_eq!(super::fix(MULTILINE, &settings), Ok(MULTILINE.to_string()));
+        assert_eq!(super::fix(INLINE, &settings), Ok(INLINE.to_string()));
+        settings.package.description.no_trailing_period = false;
+        assert_eq!(super::fix(SOURCE, &settings), Ok(SOURCE.to_string()));
+    }
+
+    // Temporary directory (removed on drop).
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Result<Self> {
+            let path = env::temp_dir().join(format!("cargo-pretty-{}-{}", name, process::id()));
+            let _ = remove_dir_all(&path);
+            create_dir_all(&path)?;
+            Ok(Self(path.canonicalize()?))
+        }
+
+        // Writes the file (relative to the directory).
+        fn file(&self, file: &str, contents: &str) -> Result<PathBuf> {
+            let path = self.0.join(file);
+            create_dir_all(dir(&path))?;
+            write(&path, contents)?;
+            Ok(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn workspace() -> Result<()> {
+        const ROOT: &str = r#"
+            [workspace]
+            members = ["crates/*", "tool"]
+            exclude = ["crates/excluded"]
+        "#;
+        let temp = TempDir::new("workspace")?;
+        let root = temp.file("Cargo.toml", ROOT)?;
+        let a = temp.file("crates/a/Cargo.toml", "")?;
+        let b = temp.file("crates/b/Cargo.toml", "")?;
+        temp.file("crates/excluded/Cargo.toml", "")?;
+        temp.file("crates/empty/README.md", "")?;
+        let tool = temp.file("tool/Cargo.toml", "")?;
+        assert_eq!(super::workspace(&root)?, vec![root, a.clone(), b, tool]);
+        assert!(super::workspace(&a).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resolver() -> Result<()> {
+        let temp = TempDir::new("resolver")?;
+        temp.file("manifestfmt.toml", "max-width = 80")?;
+        let a = temp.file("a/Cargo.toml", "")?;
+        let b = temp.file("b/Cargo.toml", "")?;
+        temp.file("b/.manifestfmt.toml", "max-width = 120")?;
+        let mut resolver = Resolver::default();
+        let root = resolver.resolve(&temp.0)?;
+        let a = resolver.resolve(dir(&a))?;
+        let b = resolver.resolve(dir(&b))?;
+        assert_eq!(root.max_width, 80);
+        assert_eq!(b.max_width, 120);
+        // The directories with the same config file share the settings.
+        assert!(Rc::ptr_eq(&root, &a));
+        assert!(Rc::ptr_eq(
+            &a,
+            &resolver.resolve(dir(&temp.0.join("a/Cargo.toml")))?
+        ));
+        assert_eq!(resolver.dirs.len(), 3);
+        assert_eq!(resolver.config_files.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn resolver_default() -> Result<()> {
+        let temp = TempDir::new("resolver-default")?;
+        let mut resolver = Resolver::default();
+        assert_eq!(
+            resolver.resolve(&temp.0)?.max_width,
+            Settings::default().max_width
+        );
+        assert!(resolver.resolve(&temp.0.join("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn extends() -> Result<()> {
+        let temp = TempDir::new("extends")?;
+        temp.file(
+            "shared/base.toml",
+            "max-width = 80\n[package]\ninline = \"None\"",
+        )?;
+        let config_file = temp.file(
+            "crate/manifestfmt.toml",
+            "extends = \"../shared/base.toml\"\nmax-width = 120",
+        )?;
+        let settings = read_settings(&config_file)?;
+        assert_eq!(settings.max_width, 120);
+        assert_eq!(settings.package.inline, Inline::Manual(None));
+        Ok(())
+    }
+
+    #[test]
+    fn inherit() -> Result<()> {
+        let temp = TempDir::new("inherit")?;
+        temp.file("manifestfmt.toml", "max-width = 80\ninherit = true")?;
+        temp.file(
+            "a/.manifestfmt.toml",
+            "inherit = true\n[package]\ninline = \"None\"",
+        )?;
+        let config_file = temp.file("a/b/manifestfmt.toml", "inherit = true")?;
+        let settings = read_settings(&config_file)?;
+        assert_eq!(settings.max_width, 80);
+        assert_eq!(settings.package.inline, Inline::Manual(None));
+        Ok(())
+    }
+
+    #[test]
+    fn extends_cycle() -> Result<()> {
+        let temp = TempDir::new("extends-cycle")?;
+        let a = temp.file("a.toml", "extends = \"b.toml\"")?;
+        let b = temp.file("b.toml", "extends = \"a.toml\"")?;
+        let err = read_settings(&a).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "config files extend each other: {} -> {} -> {}",
+                a.display(),
+                b.display(),
+                a.display(),
+            ),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn extends_missing() -> Result<()> {
+        let temp = TempDir::new("extends-missing")?;
+        let config_file = temp.file("manifestfmt.toml", "extends = \"base.toml\"")?;
+        let err = read_settings(&config_file).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "can't find the config file {} (extended by {})",
+                temp.0.join("base.toml").display(),
+                config_file.display(),
+            ),
+        );
+        Ok(())
+    }
+}
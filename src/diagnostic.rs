@@ -0,0 +1,220 @@
+use lalrpop_util::ParseError;
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::Range,
+    path::Path,
+};
+
+/// Diagnostic level.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Diagnostic.
+///
+/// Points to the source either by the span (byte offsets) or by the path of
+/// the manifest value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub level: Level,
+    pub message: String,
+    pub span: Option<Range<usize>>,
+    pub path: Option<String>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            span: None,
+            path: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Level::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Level::Warning, message)
+    }
+
+    pub fn span(mut self, span: Range<usize>) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Displays the diagnostic for the manifest file with the source.
+    pub fn display<'a>(&'a self, file: &'a Path, source: &'a str) -> impl 'a + Display {
+        Displayed {
+            diagnostic: self,
+            file,
+            source,
+        }
+    }
+}
+
+impl<T: Display, E: Display> From<ParseError<usize, T, E>> for Diagnostic {
+    fn from(from: ParseError<usize, T, E>) -> Self {
+        fn with_expected(diagnostic: Diagnostic, expected: Vec<String>) -> Diagnostic {
+            match expected.len() {
+                0 => diagnostic,
+                1 => diagnostic.note(format!("expected {}", expected[0])),
+                _ => diagnostic.note(format!("expected one of {}", expected.join(", "))),
+            }
+        }
+
+        match from {
+            ParseError::InvalidToken { location } => {
+                Self::error("invalid token").span(location..location)
+            }
+            ParseError::UnrecognizedEOF { location, expected } => with_expected(
+                Self::error("unexpected end of file").span(location..location),
+                expected,
+            ),
+            ParseError::UnrecognizedToken {
+                token: (start, token, end),
+                expected,
+            } => with_expected(
+                Self::error(format!("unexpected token `{}`", token)).span(start..end),
+                expected,
+            ),
+            ParseError::ExtraToken {
+                token: (start, token, end),
+            } => Self::error(format!("extra token `{}`", token)).span(start..end),
+            ParseError::User { error } => Self::error(error.to_string()),
+        }
+    }
+}
+
+struct Displayed<'a> {
+    diagnostic: &'a Diagnostic,
+    file: &'a Path,
+    source: &'a str,
+}
+
+impl Display for Displayed<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let Self {
+            diagnostic,
+            file,
+            source,
+        } = self;
+        writeln!(f, "{}: {}", diagnostic.level, diagnostic.message)?;
+        let margin = match &diagnostic.span {
+            Some(span) => {
+                let start = span.start.min(source.len());
+                let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+                let line_end = source[start..]
+                    .find('\n')
+                    .map_or(source.len(), |index| start + index);
+                let line = &source[line_start..line_end];
+                let number = source[..line_start].matches('\n').count() + 1;
+                let column = source[line_start..start].chars().count();
+                let end = span.end.max(start).min(line_end);
+                let carets = source[start..end].chars().count().max(1);
+                let margin = " ".repeat(number.to_string().len());
+                writeln!(
+                    f,
+                    "{}--> {}:{}:{}",
+                    margin,
+                    file.display(),
+                    number,
+                    column + 1
+                )?;
+                writeln!(f, "{} |", margin)?;
+                writeln!(f, "{} | {}", number, line.trim_end_matches('\r'))?;
+                writeln!(
+                    f,
+                    "{} | {}{}",
+                    margin,
+                    " ".repeat(column),
+                    "^".repeat(carets)
+                )?;
+                margin
+            }
+            None => {
+                match &diagnostic.path {
+                    Some(path) => writeln!(f, " --> {}: `{}`", file.display(), path)?,
+                    None => writeln!(f, " --> {}", file.display())?,
+                }
+                " ".to_string()
+            }
+        };
+        for note in &diagnostic.notes {
+            writeln!(f, "{} = {}", margin, note)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn span() {
+        const SOURCE: &str = "[package]\nname = = \"a\"\n";
+        let diagnostic = Diagnostic::error("unexpected token `=`")
+            .span(17..18)
+            .note("expected one of `\"`, `'`");
+        assert_eq!(
+            diagnostic
+                .display(Path::new("Cargo.toml"), SOURCE)
+                .to_string(),
+            "error: unexpected token `=`\n \
+             --> Cargo.toml:2:8\n  \
+             |\n\
+             2 | name = = \"a\"\n  \
+             |        ^\n  \
+             = expected one of `\"`, `'`\n",
+        );
+    }
+
+    #[test]
+    fn path() {
+        let diagnostic = Diagnostic::warning("unknown feature `b`").path("features.a.[0]");
+        assert_eq!(
+            diagnostic.display(Path::new("Cargo.toml"), "").to_string(),
+            "warning: unknown feature `b`\n --> Cargo.toml: `features.a.[0]`\n",
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        let error: ParseError<usize, &str, &str> = ParseError::UnrecognizedEOF {
+            location: 9,
+            expected: vec!["\"]\"".to_string()],
+        };
+        assert_eq!(
+            Diagnostic::from(error),
+            Diagnostic::error("unexpected end of file")
+                .span(9..9)
+                .note("expected \"]\""),
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use similar::{ChangeTag, TextDiff};
+use std::{
+    io::{Result, Write},
+    path::Path,
+};
+
+const CONTEXT_RADIUS: usize = 3;
+
+/// Diff style.
+struct Style {
+    header: &'static str,
+    hunk: &'static str,
+    delete: &'static str,
+    insert: &'static str,
+    reset: &'static str,
+}
+
+impl Style {
+    fn new(color: bool) -> Self {
+        if color {
+            Self {
+                header: "\x1b[1m",
+                hunk: "\x1b[36m",
+                delete: "\x1b[31m",
+                insert: "\x1b[32m",
+                reset: "\x1b[0m",
+            }
+        } else {
+            Self {
+                header: "",
+                hunk: "",
+                delete: "",
+                insert: "",
+                reset: "",
+            }
+        }
+    }
+}
+
+/// Writes the unified diff between the source and the target of the manifest
+/// file. Nothing is written if they are equal.
+pub(crate) fn diff<W: Write>(
+    mut writer: W,
+    path: &Path,
+    source: &str,
+    target: &str,
+    color: bool,
+) -> Result<()> {
+    let text_diff = TextDiff::from_lines(source, target);
+    let mut unified_diff = text_diff.unified_diff();
+    unified_diff.context_radius(CONTEXT_RADIUS);
+    let mut hunks = unified_diff.iter_hunks().peekable();
+    if hunks.peek().is_none() {
+        return Ok(());
+    }
+    let style = Style::new(color);
+    writeln!(
+        writer,
+        "{}--- a/{}{}",
+        style.header,
+        path.display(),
+        style.reset
+    )?;
+    writeln!(
+        writer,
+        "{}+++ b/{}{}",
+        style.header,
+        path.display(),
+        style.reset
+    )?;
+    for hunk in hunks {
+        writeln!(writer, "{}{}{}", style.hunk, hunk.header(), style.reset)?;
+        for change in hunk.iter_changes() {
+            let (sign, color) = match change.tag() {
+                ChangeTag::Equal => (' ', ""),
+                ChangeTag::Delete => ('-', style.delete),
+                ChangeTag::Insert => ('+', style.insert),
+            };
+            let value = change.value();
+            let line = value.strip_suffix('\n').unwrap_or(value);
+            let reset = if color.is_empty() { "" } else { style.reset };
+            writeln!(writer, "{}{}{}{}", color, sign, line, reset)?;
+            if change.missing_newline() {
+                writeln!(writer, "\\ No newline at end of file")?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SOURCE: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+
+    fn diff(source: &str, target: &str, color: bool) -> Result<String> {
+        let mut output = Vec::new();
+        super::diff(&mut output, Path::new("Cargo.toml"), source, target, color)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn equal() -> Result<()> {
+        assert_eq!(diff(SOURCE, SOURCE, false)?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn hunks() -> Result<()> {
+        let target = SOURCE.replace("b\n", "B\n").replace("l\n", "L\n");
+        assert_eq!(
+            diff(SOURCE, &target, false)?,
+            "--- a/Cargo.toml\n\
+             +++ b/Cargo.toml\n\
+             @@ -1,5 +1,5 @@\n \
+             a\n\
+             -b\n\
+             +B\n \
+             c\n \
+             d\n \
+             e\n\
+             @@ -9,5 +9,5 @@\n \
+             i\n \
+             j\n \
+             k\n\
+             -l\n\
+             +L\n \
+             m\n",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn color() -> Result<()> {
+        assert_eq!(
+            diff("a\nb\n", "a\nc\n", true)?,
+            "\x1b[1m--- a/Cargo.toml\x1b[0m\n\
+             \x1b[1m+++ b/Cargo.toml\x1b[0m\n\
+             \x1b[36m@@ -1,2 +1,2 @@\x1b[0m\n \
+             a\n\
+             \x1b[31m-b\x1b[0m\n\
+             \x1b[32m+c\x1b[0m\n",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_newline() -> Result<()> {
+        assert_eq!(
+            diff("a\nb", "a\nb\n", false)?,
+            "--- a/Cargo.toml\n\
+             +++ b/Cargo.toml\n\
+             @@ -1,2 +1,2 @@\n \
+             a\n\
+             -b\n\
+             \\ No newline at end of file\n\
+             +b\n",
+        );
+        Ok(())
+    }
+}
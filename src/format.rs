@@ -1,9 +1,21 @@
-use crate::{settings::Settings, sort::Sort};
+use crate::{
+    cfg,
+    diagnostic::Diagnostic,
+    inline::{self, Inline},
+    layout::{self, Arrays, Dotted, Group, Groups, Inherited, Layout, Source},
+    order::Order,
+    settings::{lints::SortBy, Lints, Settings},
+    sort::Sort,
+};
 use optional_index::OptionalIndexMut;
-use std::fmt::Display;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    mem,
+};
 use toml_lalrpop::{
     format::Independent,
-    value::{Item, Table},
+    value::{Item, Table, Value},
 };
 
 // Array of tables iterator.
@@ -15,6 +27,285 @@ fn array_of_tables(optional_item: Option<&mut Item>) -> impl Iterator<Item = &mu
         .flatten()
 }
 
+// Sorts the lints tables: tools first, then the lints of each tool.
+fn lints(mut optional_item: Option<&mut Item>, settings: &Lints) {
+    // Level (strictest first) and priority of the `lint = "level"` or the
+    // `lint = { level = "level", priority = 0 }` entry.
+    fn lint(item: &Item) -> (usize, i64) {
+        let (level, priority) = match &item.value {
+            Value::Table(table) => (
+                table.get("level").map(|item| inline::value(&item.value)),
+                table.get("priority").map(|item| inline::value(&item.value)),
+            ),
+            value => (Some(inline::value(value)), None),
+        };
+        let level = level.unwrap_or_default();
+        let level = match level.trim_matches(|c| c == '"' || c == '\'') {
+            "forbid" => 0,
+            "deny" => 1,
+            "warn" => 2,
+            "allow" => 3,
+            _ => 4,
+        };
+        let priority = priority
+            .and_then(|priority| priority.parse().ok())
+            .unwrap_or(0);
+        (level, priority)
+    }
+
+    optional_item.sort(&settings.order);
+    for (_, tool) in table(optional_item) {
+        tool.sort(&settings.tool.order);
+        if let Some(tool) = tool.as_table_mut() {
+            match settings.tool.sort_by {
+                SortBy::Name => {}
+                SortBy::Level => tool.sort_by(|_, a, _, b| lint(a).0.cmp(&lint(b).0)),
+                SortBy::Priority => tool.sort_by(|_, a, _, b| lint(a).1.cmp(&lint(b).1)),
+            }
+        }
+    }
+}
+
+// Canonicalizes the `cfg(...)` expressions of the targets and sorts them
+// semantically. Semantically identical targets keep their keys.
+fn canonicalize(optional_item: Option<&mut Item>) {
+    if let Some(targets) = optional_item.and_then(|item| item.as_table_mut()) {
+        let keys: Vec<&str> = targets.keys().map(String::as_str).collect();
+        let keys = cfg::canonical_keys(&keys);
+        for (key, (_, item)) in keys.into_iter().zip(mem::take(targets)) {
+            targets.insert(key, item);
+        }
+        targets.sort_by(|a, _, b, _| cfg::key(a).cmp(&cfg::key(b)));
+    }
+}
+
+impl cfg::Merge for Item {
+    fn is_table(&self) -> bool {
+        matches!(self.value, Value::Table(_))
+    }
+
+    fn entries(self) -> Result<Vec<(String, Self)>, Self> {
+        if !cfg::Merge::is_table(&self) {
+            return Err(self);
+        }
+        match self.value {
+            Value::Table(table) => Ok(table.into_iter().collect()),
+            _ => unreachable!(),
+        }
+    }
+
+    fn entry(&mut self, key: &str) -> Option<&mut Self> {
+        self.as_table_mut()?.get_mut(key)
+    }
+
+    fn insert(&mut self, key: String, value: Self) {
+        if let Some(table) = self.as_table_mut() {
+            table.insert(key, value);
+        }
+    }
+
+    fn same(&self, other: &Self) -> bool {
+        equal(&self.value, &other.value)
+    }
+}
+
+// Whether the values are equal as parsed (regardless of the order of the
+// table keys and of the form of the primitives, e.g. `'a'` and `"a"`).
+fn equal(a: &Value, b: &Value) -> bool {
+    match (inline::parse(a), inline::parse(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => inline::value(a) == inline::value(b),
+    }
+}
+
+// Groups the dependencies by source. Keeps the order inside each group.
+fn group(optional_item: Option<&mut Item>, group: Group) {
+    if group == Group::None {
+        return;
+    }
+    if let Some(dependencies) = optional_item.and_then(|item| item.as_table_mut()) {
+        let source = |item: &Item| {
+            Source::new(|key| matches!(&item.value, Value::Table(spec) if spec.contains_key(key)))
+        };
+        dependencies.sort_by(|_, a, _, b| source(a).cmp(&source(b)));
+    }
+}
+
+// Widths of the `key = value` lines with the inline tables.
+fn widths(table: &Table) -> HashMap<Vec<String>, usize> {
+    fn walk(table: &Table, path: &mut Vec<String>, widths: &mut HashMap<Vec<String>, usize>) {
+        for (key, item) in table.iter() {
+            if let Value::Table(child) = &item.value {
+                path.push(key.clone());
+                widths.insert(path.clone(), inline::width(key, &item.value));
+                walk(child, path, widths);
+                path.pop();
+            }
+        }
+    }
+
+    let mut widths = HashMap::new();
+    walk(table, &mut Vec::new(), &mut widths);
+    widths
+}
+
+// Tables with commented descendants. Inlining them would lose the comments.
+fn commented(table: &mut Table) -> HashSet<Vec<String>> {
+    fn walk(table: &mut Table, path: &mut Vec<String>, commented: &mut HashSet<Vec<String>>) {
+        for (key, item) in table.iter_mut() {
+            if let Value::Table(child) = &mut item.value {
+                path.push(key.clone());
+                let expanded = Independent::new(&mut *child, |_: &[&str]| false).to_string();
+                if expanded.lines().any(|line| layout::comment(line).is_some()) {
+                    commented.insert(path.clone());
+                }
+                walk(child, path, commented);
+                path.pop();
+            }
+        }
+    }
+
+    let mut commented = HashSet::new();
+    walk(table, &mut Vec::new(), &mut commented);
+    commented
+}
+
+// Inherited fields (`key = { workspace = true }`) of the package and the
+// dependency specs. They are inlined unless their inline setting is "None".
+fn inherited(table: &Table) -> HashSet<Vec<String>> {
+    fn walk(table: &Table, path: &mut Vec<String>, inherited: &mut HashSet<Vec<String>>) {
+        for (key, item) in table.iter() {
+            if let Value::Table(child) = &item.value {
+                path.push(key.clone());
+                if let Some(Value::Primitive(primitive)) =
+                    child.get("workspace").map(|item| &item.value)
+                {
+                    let keys: Vec<&str> = path.iter().map(String::as_str).collect();
+                    if primitive.to_string() == "true" && layout::inheritable(&keys) {
+                        inherited.insert(path.clone());
+                    }
+                }
+                walk(child, path, inherited);
+                path.pop();
+            }
+        }
+    }
+
+    let mut inherited = HashSet::new();
+    walk(table, &mut Vec::new(), &mut inherited);
+    inherited
+}
+
+// Inline mode at the level of the section. `Inline::Auto` never inlines the
+// section itself.
+fn level(inline: Inline, level: usize) -> Inline {
+    match inline {
+        Inline::Auto if level == 0 => Inline::Manual(None),
+        inline => inline.level(level),
+    }
+}
+
+/// Array settings.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Array<'a> {
+    pub(crate) order: &'a Order,
+    pub(crate) layout: Layout,
+    pub(crate) trailing_comma: bool,
+}
+
+/// Settings of the array at the path (the keys, without the indices of the
+/// arrays of tables). `None` if the array is never sorted nor laid out.
+pub(crate) fn array<'a>(settings: &'a Settings, path: &[&str]) -> Option<Array<'a>> {
+    let (order, layout, trailing_comma) = match path {
+        ["package", "authors"] => {
+            let array = &settings.package.authors;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["package", "keywords"] => {
+            let array = &settings.package.keywords;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["package", "categories"] => {
+            let array = &settings.package.categories;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["package", "exclude"] => {
+            let array = &settings.package.exclude;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["package", "include"] => {
+            let array = &settings.package.include;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["lib", "crate-type"] => {
+            let array = &settings.lib.crate_type;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["bin", "required-features"] => {
+            let array = &settings.bin.required_features;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["example", "required-features"] => {
+            let array = &settings.example.required_features;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["test", "required-features"] => {
+            let array = &settings.test.required_features;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["bench", "crate-type"] => {
+            let array = &settings.bench.crate_type;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["bench", "required-features"] => {
+            let array = &settings.bench.required_features;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["features", _] => {
+            let array = &settings.features.feature;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "members"] => {
+            let array = &settings.workspace.members;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "default-members"] => {
+            let array = &settings.workspace.default_members;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "exclude"] => {
+            let array = &settings.workspace.exclude;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "package", "authors"] => {
+            let array = &settings.workspace.package.authors;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "package", "keywords"] => {
+            let array = &settings.workspace.package.keywords;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "package", "categories"] => {
+            let array = &settings.workspace.package.categories;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "package", "exclude"] => {
+            let array = &settings.workspace.package.exclude;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        ["workspace", "package", "include"] => {
+            let array = &settings.workspace.package.include;
+            (&array.order, array.layout, array.trailing_comma)
+        }
+        _ => return None,
+    };
+    Some(Array {
+        order,
+        layout,
+        trailing_comma,
+    })
+}
+
 // Table iterator.
 fn table(optional_item: Option<&mut Item>) -> impl Iterator<Item = (&String, &mut Item)> {
     optional_item
@@ -36,8 +327,51 @@ pub trait Format {
     fn inline<'a>(&'a mut self, settings: &'a Settings) -> Box<dyn 'a + Display>;
 }
 
+/// Merges the semantically identical targets of the manifest
+/// (`targets.merge`). Fails if the same dependency has conflicting specs, the
+/// manifest is left as is then.
+///
+/// `Format::format` merges the targets as well, but leaves the conflicting
+/// ones as is. Call this first to report the conflicts.
+pub fn merge_targets(manifest: &mut Table, settings: &Settings) -> Result<(), Diagnostic> {
+    if !settings.targets.merge {
+        return Ok(());
+    }
+    let targets = match manifest.optional_index_mut("target") {
+        Some(targets) => targets,
+        None => return Ok(()),
+    };
+    // The conflicts are found on the parsed copy of the targets, so the
+    // manifest isn't changed on failure.
+    let parsed = match inline::parse(&targets.value) {
+        Some(toml::Value::Table(parsed)) => parsed,
+        _ => return Err(Diagnostic::error("can't merge the targets").path("target")),
+    };
+    let (_, conflicts) = cfg::merge(parsed.into_iter().collect());
+    if let Some(keys) = conflicts.first() {
+        let diagnostic = Diagnostic::error(format!("target `{}` has conflicting specs", keys[0]))
+            .path(cfg::path(keys));
+        return Err(match keys.get(2) {
+            Some(name) => {
+                diagnostic.note(format!("dependency `{}` is specified differently", name))
+            }
+            None => diagnostic,
+        });
+    }
+    if let Some(targets) = targets.as_table_mut() {
+        let (merged, _) = cfg::merge(mem::take(targets).into_iter().collect());
+        for (target, item) in merged {
+            targets.insert(target, item);
+        }
+    }
+    Ok(())
+}
+
 impl Format for Table {
     fn sort(&mut self, settings: &Settings) {
+        // The conflicting targets are left as is (`merge_targets` reports
+        // them).
+        let _ = merge_targets(self, settings);
         Sort::sort(self, &settings.order);
         // package.
         let mut package = self.optional_index_mut("package");
@@ -99,26 +433,66 @@ impl Format for Table {
             // dependencies.
             let mut dependencies = self.optional_index_mut("dependencies");
             dependencies.sort(&settings.dependencies.order);
+            group(dependencies.as_deref_mut(), settings.dependencies.group);
             for (_, dependency) in table(dependencies) {
                 dependency.sort(&settings.dependencies.dependency.order);
             }
             // dev-dependencies.
             let mut dev_dependencies = self.optional_index_mut("dev-dependencies");
             dev_dependencies.sort(&settings.dev_dependencies.order);
+            group(
+                dev_dependencies.as_deref_mut(),
+                settings.dev_dependencies.group,
+            );
             for (_, dependency) in table(dev_dependencies) {
                 dependency.sort(&settings.dev_dependencies.dependency.order);
             }
             // build-dependencies.
             let mut build_dependencies = self.optional_index_mut("build-dependencies");
             build_dependencies.sort(&settings.build_dependencies.order);
+            group(
+                build_dependencies.as_deref_mut(),
+                settings.build_dependencies.group,
+            );
             for (_, dependency) in table(build_dependencies) {
                 dependency.sort(&settings.build_dependencies.dependency.order);
             }
             // target.
             let mut targets = self.optional_index_mut("target");
-            targets.sort(&settings.targets.order);
+            if settings.targets.canonicalize {
+                canonicalize(targets.as_deref_mut());
+            } else {
+                targets.sort(&settings.targets.order);
+            }
             for (_, target) in table(targets) {
                 target.sort(&settings.targets.target.order);
+                let mut dependencies = target.optional_index_mut("dependencies");
+                dependencies.sort(&settings.targets.dependencies.order);
+                group(
+                    dependencies.as_deref_mut(),
+                    settings.targets.dependencies.group,
+                );
+                for (_, dependency) in table(dependencies) {
+                    dependency.sort(&settings.targets.dependencies.dependency.order);
+                }
+                let mut dev_dependencies = target.optional_index_mut("dev-dependencies");
+                dev_dependencies.sort(&settings.targets.dev_dependencies.order);
+                group(
+                    dev_dependencies.as_deref_mut(),
+                    settings.targets.dev_dependencies.group,
+                );
+                for (_, dependency) in table(dev_dependencies) {
+                    dependency.sort(&settings.targets.dev_dependencies.dependency.order);
+                }
+                let mut build_dependencies = target.optional_index_mut("build-dependencies");
+                build_dependencies.sort(&settings.targets.build_dependencies.order);
+                group(
+                    build_dependencies.as_deref_mut(),
+                    settings.targets.build_dependencies.group,
+                );
+                for (_, dependency) in table(build_dependencies) {
+                    dependency.sort(&settings.targets.build_dependencies.dependency.order);
+                }
             }
         }
         // badges.
@@ -133,6 +507,8 @@ impl Format for Table {
         for (_, feature) in table(features) {
             feature.sort(&settings.features.feature.order);
         }
+        // lints.
+        lints(self.optional_index_mut("lints"), &settings.lints);
         // patch.
         self.optional_index_mut("patch").sort(&settings.patch.order);
         // replace.
@@ -156,53 +532,112 @@ impl Format for Table {
         workspace
             .optional_index_mut("exclude")
             .sort(&settings.workspace.exclude.order);
+        let mut workspace_package = workspace.optional_index_mut("package");
+        workspace_package.sort(&settings.workspace.package.order);
+        workspace_package
+            .optional_index_mut("authors")
+            .sort(&settings.workspace.package.authors.order);
+        workspace_package
+            .optional_index_mut("keywords")
+            .sort(&settings.workspace.package.keywords.order);
+        workspace_package
+            .optional_index_mut("categories")
+            .sort(&settings.workspace.package.categories.order);
+        workspace_package
+            .optional_index_mut("exclude")
+            .sort(&settings.workspace.package.exclude.order);
+        workspace_package
+            .optional_index_mut("include")
+            .sort(&settings.workspace.package.include.order);
+        workspace
+            .optional_index_mut("metadata")
+            .sort(&settings.workspace.metadata.order);
+        let mut workspace_dependencies = workspace.optional_index_mut("dependencies");
+        workspace_dependencies.sort(&settings.workspace.dependencies.order);
+        group(
+            workspace_dependencies.as_deref_mut(),
+            settings.workspace.dependencies.group,
+        );
+        for (_, dependency) in table(workspace_dependencies) {
+            dependency.sort(&settings.workspace.dependencies.dependency.order);
+        }
+        lints(
+            workspace.optional_index_mut("lints"),
+            &settings.workspace.lints,
+        );
     }
 
     fn inline<'a>(&'a mut self, settings: &'a Settings) -> Box<dyn 'a + Display> {
-        let is_inline = move |key: &[&str]| match key {
-            ["profile", rest @ ..] => settings.profiles.inline.level(rest.len()).is_inline(),
-            ["patch", rest @ ..] => settings.patch.inline.level(rest.len()).is_inline(),
-            ["badges", rest @ ..] => settings.badges.inline.level(rest.len()).is_inline(),
-            ["target", _, "build-dependencies", rest @ ..] => settings
-                .targets
-                .build_dependencies
-                .inline
-                .level(rest.len())
-                .is_inline(),
-            ["target", _, "dev-dependencies", rest @ ..] => settings
-                .targets
-                .dev_dependencies
-                .inline
-                .level(rest.len())
-                .is_inline(),
-            ["target", _, "dependencies", rest @ ..] => settings
-                .targets
-                .dependencies
-                .inline
-                .level(rest.len())
-                .is_inline(),
-            ["build-dependencies", rest @ ..] => settings
-                .build_dependencies
-                .inline
-                .level(rest.len())
-                .is_inline(),
-            ["dev-dependencies", rest @ ..] => settings
-                .dev_dependencies
-                .inline
-                .level(rest.len())
-                .is_inline(),
-            ["dependencies", rest @ ..] => {
-                settings.dependencies.inline.level(rest.len()).is_inline()
-            }
-            ["package", "metadata", rest @ ..] => settings
-                .package
-                .metadata
-                .inline
-                .level(rest.len())
-                .is_inline(),
-            ["package", rest @ ..] => settings.package.inline.level(rest.len()).is_inline(),
-            _ => false,
+        let inline = move |key: &[&str]| match key {
+            ["profile", rest @ ..] => level(settings.profiles.inline, rest.len()),
+            ["patch", rest @ ..] => level(settings.patch.inline, rest.len()),
+            ["lints", rest @ ..] => level(settings.lints.inline, rest.len()),
+            ["badges", rest @ ..] => level(settings.badges.inline, rest.len()),
+            ["target", _, "build-dependencies", rest @ ..] => {
+                level(settings.targets.build_dependencies.inline, rest.len())
+            }
+            ["target", _, "dev-dependencies", rest @ ..] => {
+                level(settings.targets.dev_dependencies.inline, rest.len())
+            }
+            ["target", _, "dependencies", rest @ ..] => {
+                level(settings.targets.dependencies.inline, rest.len())
+            }
+            ["build-dependencies", rest @ ..] => {
+                level(settings.build_dependencies.inline, rest.len())
+            }
+            ["dev-dependencies", rest @ ..] => level(settings.dev_dependencies.inline, rest.len()),
+            ["dependencies", rest @ ..] => level(settings.dependencies.inline, rest.len()),
+            ["workspace", "dependencies", rest @ ..] => {
+                level(settings.workspace.dependencies.inline, rest.len())
+            }
+            ["workspace", "package", rest @ ..] => {
+                level(settings.workspace.package.inline, rest.len())
+            }
+            ["workspace", "lints", rest @ ..] => level(settings.workspace.lints.inline, rest.len()),
+            ["workspace", "metadata", rest @ ..] => {
+                level(settings.workspace.metadata.inline, rest.len())
+            }
+            ["package", "metadata", rest @ ..] => {
+                level(settings.package.metadata.inline, rest.len())
+            }
+            ["package", rest @ ..] => level(settings.package.inline, rest.len()),
+            _ => Inline::Manual(None),
+        };
+        let widths = widths(self);
+        let commented = commented(self);
+        let inherited = inherited(self);
+        let is_inline = move |key: &[&str]| {
+            let path = key.iter().map(ToString::to_string).collect::<Vec<_>>();
+            let width = || widths.get(&path).copied().unwrap_or(usize::MAX);
+            !commented.contains(&path)
+                && match inline(key) {
+                    Inline::Manual(None) => false,
+                    _ if inherited.contains(&path) => true,
+                    inline => inline.is_inline_within(width, settings.max_width),
+                }
         };
-        Box::new(Independent::new(self, is_inline))
+        let layout = move |key: &[&str]| {
+            array(settings, key).map(|array| (array.layout, array.trailing_comma))
+        };
+        let group = move |key: &[&str]| match key {
+            ["dependencies"] => settings.dependencies.group,
+            ["dev-dependencies"] => settings.dev_dependencies.group,
+            ["build-dependencies"] => settings.build_dependencies.group,
+            ["target", _, "dependencies"] => settings.targets.dependencies.group,
+            ["target", _, "dev-dependencies"] => settings.targets.dev_dependencies.group,
+            ["target", _, "build-dependencies"] => settings.targets.build_dependencies.group,
+            ["workspace", "dependencies"] => settings.workspace.dependencies.group,
+            _ => Group::None,
+        };
+        let arrays = Arrays::new(
+            Independent::new(self, is_inline),
+            layout,
+            settings.max_width,
+        );
+        let groups = Groups::new(arrays, group);
+        match settings.inherited {
+            Inherited::Inline => Box::new(groups),
+            Inherited::Dotted => Box::new(Dotted::new(groups)),
+        }
     }
 }
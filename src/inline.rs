@@ -2,13 +2,15 @@ use itertools::Itertools;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_diff::SerdeDiff;
 use std::{
+    borrow::Cow,
     convert::TryFrom,
     fmt::{self, Formatter},
 };
+use toml_lalrpop::value::Value;
 
 /// Inline.
 ///
-/// - `Inline::Auto` - TODO:,
+/// - `Inline::Auto` => inline if the line fits the max width,
 /// - `Inline::Manual(None)` => never inline ("None"),
 /// - `Inline::Manual(Some(0))` => inline starting with self (level 0),
 /// - `Inline::Manual(Some(1))` => inline starting with children (level 1),
@@ -43,6 +45,15 @@ impl Inline {
             _ => false,
         }
     }
+
+    /// Same as `is_inline`, but `Inline::Auto` inlines only if the width of the
+    /// line fits the max width.
+    pub fn is_inline_within(&self, width: impl FnOnce() -> usize, max_width: usize) -> bool {
+        match self {
+            Self::Auto => width() <= max_width,
+            inline => inline.is_inline(),
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for Inline {
@@ -100,3 +111,55 @@ impl Serialize for Inline {
         }
     }
 }
+
+/// Key segment as it is written in the manifest.
+pub(crate) fn key(key: &str) -> Cow<str> {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        Cow::Borrowed(key)
+    } else {
+        Cow::Owned(format!(
+            "\"{}\"",
+            key.replace('\\', "\\\\").replace('"', "\\\"")
+        ))
+    }
+}
+
+/// Value as it is written inline in the manifest.
+pub(crate) fn value(value: &Value) -> String {
+    match value {
+        Value::Primitive(primitive) => primitive.to_string(),
+        Value::Array(array) => format!(
+            "[{}]",
+            array
+                .iter()
+                .format_with(", ", |item, f| f(&self::value(&item.value)))
+        ),
+        Value::Table(table) if table.is_empty() => "{}".to_string(),
+        Value::Table(table) => format!(
+            "{{ {} }}",
+            table
+                .iter()
+                .format_with(", ", |(k, item), f| f(&format_args!(
+                    "{} = {}",
+                    key(k),
+                    self::value(&item.value)
+                )))
+        ),
+    }
+}
+
+/// Value parsed from its inline form (`None` if it can't be parsed).
+pub(crate) fn parse(value: &Value) -> Option<toml::Value> {
+    let source = format!("value = {}", self::value(value));
+    let mut table: toml::value::Table = toml::from_str(&source).ok()?;
+    table.remove("value")
+}
+
+/// Width of the `key = value` line with the inline value.
+pub(crate) fn width(k: &str, v: &Value) -> usize {
+    key(k).chars().count() + " = ".len() + value(v).chars().count()
+}
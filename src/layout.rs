@@ -0,0 +1,889 @@
+use serde::{Deserialize, Serialize};
+use serde_diff::SerdeDiff;
+use std::fmt::{self, Display, Formatter};
+use toml::Value;
+
+const INDENT: &str = "    ";
+const INHERITED: &str = "{ workspace = true }";
+
+/// Array layout.
+///
+/// - `Layout::Horizontal` => always single-line,
+/// - `Layout::Vertical` => always one element per line,
+/// - `Layout::Auto` => one element per line if the single-line array doesn't
+///   fit the max width.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, SerdeDiff, Serialize)]
+pub enum Layout {
+    Horizontal,
+    Vertical,
+    Auto,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self::Horizontal
+    }
+}
+
+/// Inherited field form.
+///
+/// - `Inherited::Inline` => `version = { workspace = true }`,
+/// - `Inherited::Dotted` => `version.workspace = true`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, SerdeDiff, Serialize)]
+pub enum Inherited {
+    Inline,
+    Dotted,
+}
+
+impl Default for Inherited {
+    fn default() -> Self {
+        Self::Inline
+    }
+}
+
+/// Dependency grouping.
+///
+/// - `Group::None` => a single block,
+/// - `Group::Source` => a block for each source (workspace-inherited, path,
+///   git, registry) separated by a blank line,
+/// - `Group::SourceWithHeaders` => same as `Group::Source` with a header
+///   comment before each block.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, SerdeDiff, Serialize)]
+pub enum Group {
+    None,
+    Source,
+    SourceWithHeaders,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+/// Dependency source.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub(crate) enum Source {
+    Workspace,
+    Path,
+    Git,
+    Registry,
+}
+
+impl Source {
+    /// Source of the dependency spec with the keys.
+    pub(crate) fn new(contains_key: impl Fn(&str) -> bool) -> Self {
+        if contains_key("workspace") {
+            Self::Workspace
+        } else if contains_key("path") {
+            Self::Path
+        } else if contains_key("git") {
+            Self::Git
+        } else {
+            Self::Registry
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Workspace => "# Workspace dependencies",
+            Self::Path => "# Path dependencies",
+            Self::Git => "# Git dependencies",
+            Self::Registry => "# Registry dependencies",
+        }
+    }
+
+    // Whether the line is a generated header comment.
+    fn generated(line: &str) -> bool {
+        [Self::Workspace, Self::Path, Self::Git, Self::Registry]
+            .iter()
+            .any(|source| line.trim() == source.header())
+    }
+
+    // Source of the `name = spec` dependency line.
+    fn parse(line: &str) -> Option<Self> {
+        let value: Value = toml::from_str(line).ok()?;
+        let (_, spec) = value.as_table()?.iter().next()?;
+        Some(Self::new(|key| spec.get(key).is_some()))
+    }
+}
+
+/// Arrays.
+///
+/// Lays out the arrays of the displayed manifest (single-line or multi-line
+/// without comments) according to the layout (and the trailing comma flag)
+/// for their path.
+pub(crate) struct Arrays<D, F> {
+    display: D,
+    layout: F,
+    max_width: usize,
+}
+
+impl<D, F> Arrays<D, F> {
+    pub(crate) fn new(display: D, layout: F, max_width: usize) -> Self {
+        Self {
+            display,
+            layout,
+            max_width,
+        }
+    }
+}
+
+impl<D: Display, F: Fn(&[&str]) -> Option<(Layout, bool)>> Display for Arrays<D, F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let source = self.display.to_string();
+        let lines: Vec<&str> = source.split('\n').collect();
+        let mut section = Vec::new();
+        let mut lexer = Lexer::default();
+        let mut index = 0;
+        while index < lines.len() {
+            let line = lines[index];
+            if index != 0 {
+                writeln!(f)?;
+            }
+            index += 1;
+            if lexer.multiline_string(line) {
+                write!(f, "{}", line)?;
+                continue;
+            }
+            if let Some(keys) = header(line) {
+                section = keys;
+                write!(f, "{}", line)?;
+                continue;
+            }
+            match key_value(line) {
+                Some((keys, value)) => {
+                    let mut path: Vec<&str> = section.iter().map(String::as_str).collect();
+                    path.extend(keys.iter().map(String::as_str));
+                    let key = &line[..line.len() - value.len()];
+                    let layout = (self.layout)(&path);
+                    let joined = match layout {
+                        Some(_) if array(value).is_none() => multiline(value, &lines[index..]),
+                        _ => None,
+                    };
+                    let value: &str = match &joined {
+                        Some((joined, count)) => {
+                            index += count;
+                            joined
+                        }
+                        None => value,
+                    };
+                    match layout.zip(array(value)) {
+                        Some(((layout, trailing_comma), items)) => {
+                            let horizontal = match &joined {
+                                Some(_) => format!("{}[{}]", key, items.join(", ")),
+                                None => line.to_owned(),
+                            };
+                            let vertical = match layout {
+                                Layout::Horizontal => false,
+                                Layout::Vertical => true,
+                                Layout::Auto => horizontal.chars().count() > self.max_width,
+                            };
+                            if vertical && !items.is_empty() {
+                                writeln!(f, "{}[", key)?;
+                                for (index, item) in items.iter().enumerate() {
+                                    let comma = index + 1 != items.len() || trailing_comma;
+                                    writeln!(
+                                        f,
+                                        "{}{}{}",
+                                        INDENT,
+                                        item,
+                                        if comma { "," } else { "" }
+                                    )?;
+                                }
+                                write!(f, "]")?;
+                            } else {
+                                write!(f, "{}", horizontal)?;
+                            }
+                        }
+                        None => write!(f, "{}", line)?,
+                    }
+                }
+                None => write!(f, "{}", line)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// The multi-line array starting with the value: the value joined with the
+// following lines and the number of the joined lines. `None` if the array
+// has a comment or a multi-line string.
+fn multiline(value: &str, lines: &[&str]) -> Option<(String, usize)> {
+    if !value.starts_with('[') || scan(value, &mut None) != (None, false) {
+        return None;
+    }
+    let mut joined = value.trim_end().to_owned();
+    for (index, line) in lines.iter().enumerate() {
+        if scan(line, &mut None) != (None, false) {
+            return None;
+        }
+        joined.push_str(line.trim());
+        if array(&joined).is_some() {
+            return Some((joined, index + 1));
+        }
+    }
+    None
+}
+
+/// Whether the field at the path can be inherited from the workspace (a
+/// package field or a dependency spec of the member).
+pub(crate) fn inheritable(path: &[&str]) -> bool {
+    match path {
+        ["package", "metadata"] => false,
+        ["package", _] => true,
+        [dependencies, _] | ["target", _, dependencies, _] => matches!(
+            *dependencies,
+            "dependencies" | "dev-dependencies" | "build-dependencies"
+        ),
+        _ => false,
+    }
+}
+
+/// Dotted.
+///
+/// Writes the inline inherited fields (`key = { workspace = true }`) of the
+/// displayed manifest in the dotted form (`key.workspace = true`).
+pub(crate) struct Dotted<D> {
+    display: D,
+}
+
+impl<D> Dotted<D> {
+    pub(crate) fn new(display: D) -> Self {
+        Self { display }
+    }
+}
+
+impl<D: Display> Display for Dotted<D> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let source = self.display.to_string();
+        let mut section = Vec::new();
+        let mut lexer = Lexer::default();
+        for (index, line) in source.split('\n').enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+            if lexer.multiline_string(line) {
+                write!(f, "{}", line)?;
+                continue;
+            }
+            if let Some(keys) = header(line) {
+                section = keys;
+                write!(f, "{}", line)?;
+                continue;
+            }
+            let dotted = key_value(line).and_then(|(keys, value)| {
+                let mut path: Vec<&str> = section.iter().map(String::as_str).collect();
+                path.extend(keys.iter().map(String::as_str));
+                if !inheritable(&path) {
+                    return None;
+                }
+                let rest = value.strip_prefix(INHERITED)?;
+                let key = line[..line.len() - value.len()].trim_end();
+                let key = key.strip_suffix('=')?.trim_end();
+                Some((key, rest))
+            });
+            match dotted {
+                Some((key, rest)) => write!(f, "{}.workspace = true{}", key, rest)?,
+                None => write!(f, "{}", line)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Groups.
+///
+/// Separates the dependency blocks of the displayed manifest by a blank line
+/// (and a header comment) according to the grouping for their section. The
+/// dependencies are expected to be sorted by source already. The header
+/// comments are regenerated, so the comments equal to a header are removed
+/// from the grouped sections. The dependencies in their own sections (e.g.
+/// `[dependencies.a]`) aren't grouped.
+pub(crate) struct Groups<D, F> {
+    display: D,
+    group: F,
+}
+
+impl<D, F> Groups<D, F> {
+    pub(crate) fn new(display: D, group: F) -> Self {
+        Self { display, group }
+    }
+}
+
+impl<D: Display, F: Fn(&[&str]) -> Group> Display for Groups<D, F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let source = self.display.to_string();
+        let mut lines: Vec<&str> = Vec::new();
+        // Grouping of the dependencies table and the source of the last block.
+        let mut group = Group::None;
+        let mut previous = None;
+        // Whether the section is the grouped dependencies table or the section
+        // of one of its dependencies (`[dependencies.name]`).
+        let mut grouped = false;
+        let mut lexer = Lexer::default();
+        for line in source.split('\n') {
+            if lexer.multiline_string(line) {
+                lines.push(line);
+                continue;
+            }
+            if let Some(keys) = header(line) {
+                let path: Vec<&str> = keys.iter().map(String::as_str).collect();
+                group = (self.group)(&path);
+                previous = None;
+                let parent = path.split_last().map(|(_, parent)| (self.group)(parent));
+                grouped =
+                    group != Group::None || matches!(parent, Some(group) if group != Group::None);
+                lines.push(line);
+                continue;
+            }
+            if grouped && Source::generated(line) {
+                continue;
+            }
+            if group != Group::None && key_value(line).is_some() {
+                if let Some(source) = Source::parse(line) {
+                    block(&mut lines, group, &mut previous, source);
+                }
+            }
+            lines.push(line);
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// Starts the block of the dependencies from the source (unless the previous
+// dependency is from it) before the dependency. The comments of the
+// dependency stay with it.
+fn block(lines: &mut Vec<&str>, group: Group, previous: &mut Option<Source>, source: Source) {
+    if *previous == Some(source) {
+        return;
+    }
+    let start = lines.len()
+        - lines
+            .iter()
+            .rev()
+            .take_while(|line| comment(line) == Some(**line))
+            .count();
+    let mut block = Vec::new();
+    if previous.is_some() && lines[..start].last() != Some(&"") {
+        block.push("");
+    }
+    if group == Group::SourceWithHeaders {
+        block.push(source.header());
+    }
+    lines.splice(start..start, block);
+    *previous = Some(source);
+}
+
+/// Lexer of the displayed manifest lines.
+///
+/// Tracks the multi-line strings spanning the lines, so the post-passes keep
+/// them as is.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Lexer {
+    // Delimiter of the open multi-line string.
+    multiline: Option<&'static str>,
+}
+
+impl Lexer {
+    /// Lexes the next line. Returns whether the line is (a part of) a
+    /// multi-line string.
+    pub(crate) fn multiline_string(&mut self, line: &str) -> bool {
+        scan(line, &mut self.multiline).1
+    }
+
+    /// Whether a multi-line string opened on the previous lines isn't closed
+    /// yet.
+    pub(crate) fn inside_string(&self) -> bool {
+        self.multiline.is_some()
+    }
+}
+
+const BASIC: &str = r#"""""#;
+const LITERAL: &str = "'''";
+
+// Scans the line starting in the multi-line string with the delimiter (if
+// any). Returns the start of the comment and whether the line has a
+// multi-line string. Updates the delimiter of the open multi-line string.
+fn scan(line: &str, multiline: &mut Option<&'static str>) -> (Option<usize>, bool) {
+    let mut has_multiline = multiline.is_some();
+    let mut chars = line.char_indices();
+    while let Some((index, c)) = chars.next() {
+        let rest = &line[index..];
+        match *multiline {
+            Some(delimiter) if rest.starts_with(delimiter) => {
+                // Up to two quotes can precede the closing delimiter.
+                let quote = c;
+                let quotes = rest.chars().take_while(|&c| c == quote).count().min(5);
+                chars.nth(quotes - 2);
+                *multiline = None;
+            }
+            Some(BASIC) if c == '\\' => {
+                chars.next();
+            }
+            Some(_) => {}
+            None if rest.starts_with(BASIC) || rest.starts_with(LITERAL) => {
+                *multiline = Some(if c == '"' { BASIC } else { LITERAL });
+                has_multiline = true;
+                chars.nth(1);
+            }
+            None => match c {
+                '"' => {
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '\\' => {
+                                chars.next();
+                            }
+                            '"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                '\'' => while !matches!(chars.next(), Some((_, '\'')) | None) {},
+                '#' => return (Some(index), has_multiline),
+                _ => {}
+            },
+        }
+    }
+    (None, has_multiline)
+}
+
+// Keys of the table header (`[a.b]` or `[[a.b]]`) line.
+pub(crate) fn header(line: &str) -> Option<Vec<String>> {
+    let rest = line.strip_prefix('[')?;
+    let rest = rest.strip_prefix('[').unwrap_or(rest);
+    let (keys, rest) = keys(rest)?;
+    let rest = rest.strip_prefix(']')?;
+    let rest = rest.strip_prefix(']').unwrap_or(rest).trim_start();
+    if rest.is_empty() || rest.starts_with('#') {
+        Some(keys)
+    } else {
+        None
+    }
+}
+
+// Keys and value of the `key = value` line.
+pub(crate) fn key_value(line: &str) -> Option<(Vec<String>, &str)> {
+    let (keys, rest) = keys(line)?;
+    let value = rest.strip_prefix('=')?.trim_start();
+    Some((keys, value))
+}
+
+// Comment of the line (outside of a multi-line string).
+pub(crate) fn comment(line: &str) -> Option<&str> {
+    let (start, _) = scan(line, &mut None);
+    start.map(|start| &line[start..])
+}
+
+// Items of the single-line array without comments.
+pub(crate) fn array(value: &str) -> Option<Vec<&str>> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut chars = inner.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => loop {
+                match chars.next()?.1 {
+                    '\\' => {
+                        chars.next()?;
+                    }
+                    '"' => break,
+                    _ => {}
+                }
+            },
+            '\'' => while chars.next()?.1 != '\'' {},
+            '[' | '{' => depth += 1,
+            ']' | '}' if depth == 0 => return None,
+            ']' | '}' => depth -= 1,
+            '#' => return None,
+            ',' if depth == 0 => {
+                items.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        items.push(last);
+    }
+    Some(items)
+}
+
+// Dotted keys and the rest of the line.
+pub(crate) fn keys(mut line: &str) -> Option<(Vec<String>, &str)> {
+    let mut keys = Vec::new();
+    loop {
+        line = line.trim_start();
+        let (key, rest) = if let Some(rest) = line.strip_prefix('"') {
+            let mut key = String::new();
+            let mut chars = rest.char_indices();
+            loop {
+                match chars.next()? {
+                    (_, '\\') => key.push(chars.next()?.1),
+                    (index, '"') => break (key, &rest[index + 1..]),
+                    (_, c) => key.push(c),
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix('\'') {
+            let end = rest.find('\'')?;
+            (rest[..end].to_string(), &rest[end + 1..])
+        } else {
+            let end = line
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+                .unwrap_or(line.len());
+            if end == 0 {
+                return None;
+            }
+            (line[..end].to_string(), &line[end..])
+        };
+        keys.push(key);
+        line = rest.trim_start();
+        match line.strip_prefix('.') {
+            Some(rest) => line = rest,
+            None => return Some((keys, line)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arrays(source: &str, layout: Layout, trailing_comma: bool, max_width: usize) -> String {
+        Arrays::new(
+            source,
+            |path: &[&str]| match path {
+                ["package", "keywords"] => Some((layout, trailing_comma)),
+                _ => None,
+            },
+            max_width,
+        )
+        .to_string()
+    }
+
+    const SOURCE: &str = "[package]\n\
+        name = \"a\"\n\
+        keywords = [\"a\", \"b, c\", 'd']\n\
+        exclude = [\"a\", \"b\"]\n";
+
+    #[test]
+    fn horizontal() {
+        assert_eq!(arrays(SOURCE, Layout::Horizontal, true, 100), SOURCE);
+    }
+
+    #[test]
+    fn vertical() {
+        const TARGET: &str = "[package]\n\
+            name = \"a\"\n\
+            keywords = [\n    \"a\",\n    \"b, c\",\n    'd',\n]\n\
+            exclude = [\"a\", \"b\"]\n";
+        assert_eq!(arrays(SOURCE, Layout::Vertical, true, 100), TARGET);
+    }
+
+    #[test]
+    fn vertical_without_trailing_comma() {
+        const TARGET: &str = "[package]\n\
+            name = \"a\"\n\
+            keywords = [\n    \"a\",\n    \"b, c\",\n    'd'\n]\n\
+            exclude = [\"a\", \"b\"]\n";
+        assert_eq!(arrays(SOURCE, Layout::Vertical, false, 100), TARGET);
+    }
+
+    #[test]
+    fn auto() {
+        assert_eq!(arrays(SOURCE, Layout::Auto, true, 100), SOURCE);
+        assert_eq!(
+            arrays(SOURCE, Layout::Auto, true, 20),
+            arrays(SOURCE, Layout::Vertical, true, 100),
+        );
+    }
+
+    #[test]
+    fn multiline_array() {
+        const SOURCE: &str = "[package]\n\
+            keywords = [\n    \"a\",\n    \"b, c\",\n    'd',\n]\n\
+            exclude = [\"a\", \"b\"]\n";
+        const HORIZONTAL: &str = "[package]\n\
+            keywords = [\"a\", \"b, c\", 'd']\n\
+            exclude = [\"a\", \"b\"]\n";
+        assert_eq!(arrays(SOURCE, Layout::Horizontal, true, 100), HORIZONTAL);
+        assert_eq!(arrays(SOURCE, Layout::Auto, true, 100), HORIZONTAL);
+        assert_eq!(arrays(SOURCE, Layout::Vertical, true, 100), SOURCE);
+        assert_eq!(
+            arrays(HORIZONTAL, Layout::Vertical, true, 100),
+            arrays(SOURCE, Layout::Vertical, true, 100),
+        );
+    }
+
+    #[test]
+    fn multiline_array_commented() {
+        const SOURCE: &str = "[package]\n\
+            keywords = [\n    \"a\", # comment\n    \"b\",\n]\n";
+        assert_eq!(arrays(SOURCE, Layout::Horizontal, true, 100), SOURCE);
+        const STRING: &str = "[package]\n\
+            keywords = [\n    \"\"\"a\"\"\",\n]\n";
+        assert_eq!(arrays(STRING, Layout::Horizontal, true, 100), STRING);
+    }
+
+    #[test]
+    fn dotted() {
+        const SOURCE: &str = "[package]\n\
+            version = { workspace = true } # comment\n\
+            edition = \"2018\"\n\
+            \n\
+            [dependencies]\n\
+            a = { workspace = true, features = [\"b\"] }\n\
+            b = { workspace = true }\n\
+            \n\
+            [package.metadata]\n\
+            c = { workspace = true }\n";
+        const TARGET: &str = "[package]\n\
+            version.workspace = true # comment\n\
+            edition = \"2018\"\n\
+            \n\
+            [dependencies]\n\
+            a = { workspace = true, features = [\"b\"] }\n\
+            b.workspace = true\n\
+            \n\
+            [package.metadata]\n\
+            c = { workspace = true }\n";
+        assert_eq!(Dotted::new(SOURCE).to_string(), TARGET);
+    }
+
+    fn groups(source: &str, group: Group) -> String {
+        Groups::new(source, |path: &[&str]| match path {
+            ["dependencies"] => group,
+            _ => Group::None,
+        })
+        .to_string()
+    }
+
+    const DEPENDENCIES: &str = "[dependencies]\n\
+        a = { workspace = true }\n\
+        # comment\n\
+        b = { path = \"../b\" }\n\
+        c = { path = \"../c\" }\n\
+        d = { git = \"https://github.com/d/d\" }\n\
+        e = \"1.0.0\"\n\
+        \n\
+        [dev-dependencies]\n\
+        b = { path = \"../b\" }\n\
+        e = \"1.0.0\"\n";
+
+    #[test]
+    fn group_source() {
+        const TARGET: &str = "[dependencies]\n\
+            a = { workspace = true }\n\
+            \n\
+            # comment\n\
+            b = { path = \"../b\" }\n\
+            c = { path = \"../c\" }\n\
+            \n\
+            d = { git = \"https://github.com/d/d\" }\n\
+            \n\
+            e = \"1.0.0\"\n\
+            \n\
+            [dev-dependencies]\n\
+            b = { path = \"../b\" }\n\
+            e = \"1.0.0\"\n";
+        assert_eq!(groups(DEPENDENCIES, Group::None), DEPENDENCIES);
+        assert_eq!(groups(DEPENDENCIES, Group::Source), TARGET);
+    }
+
+    #[test]
+    fn group_source_with_headers() {
+        const TARGET: &str = "[dependencies]\n\
+            # Workspace dependencies\n\
+            a = { workspace = true }\n\
+            \n\
+            # Path dependencies\n\
+            # comment\n\
+            b = { path = \"../b\" }\n\
+            c = { path = \"../c\" }\n\
+            \n\
+            # Git dependencies\n\
+            d = { git = \"https://github.com/d/d\" }\n\
+            \n\
+            # Registry dependencies\n\
+            e = \"1.0.0\"\n\
+            \n\
+            [dev-dependencies]\n\
+            b = { path = \"../b\" }\n\
+            e = \"1.0.0\"\n";
+        assert_eq!(groups(DEPENDENCIES, Group::SourceWithHeaders), TARGET);
+        // Idempotent (the blank lines aren't kept by the parser).
+        let formatted = TARGET.replace("\n\n#", "\n#");
+        assert_eq!(groups(&formatted, Group::SourceWithHeaders), TARGET);
+    }
+
+    #[test]
+    fn group_expanded() {
+        const SOURCE: &str = "[dependencies]\n\
+            a = { workspace = true }\n\
+            e = \"1.0.0\"\n\
+            \n\
+            [dependencies.b]\n\
+            path = \"../b\"\n\
+            features = [\"f\"]\n\
+            \n\
+            # comment\n\
+            # Registry dependencies\n\
+            [dependencies.c]\n\
+            version = \"1.0.0\"\n\
+            features = [\"f\"]\n";
+        const SOURCE_TARGET: &str = "[dependencies]\n\
+            a = { workspace = true }\n\
+            \n\
+            e = \"1.0.0\"\n\
+            \n\
+            [dependencies.b]\n\
+            path = \"../b\"\n\
+            features = [\"f\"]\n\
+            \n\
+            # comment\n\
+            [dependencies.c]\n\
+            version = \"1.0.0\"\n\
+            features = [\"f\"]\n";
+        const HEADERS_TARGET: &str = "[dependencies]\n\
+            # Workspace dependencies\n\
+            a = { workspace = true }\n\
+            \n\
+            # Registry dependencies\n\
+            e = \"1.0.0\"\n\
+            \n\
+            [dependencies.b]\n\
+            path = \"../b\"\n\
+            features = [\"f\"]\n\
+            \n\
+            # comment\n\
+            [dependencies.c]\n\
+            version = \"1.0.0\"\n\
+            features = [\"f\"]\n";
+        assert_eq!(groups(SOURCE, Group::Source), SOURCE_TARGET);
+        assert_eq!(groups(SOURCE, Group::SourceWithHeaders), HEADERS_TARGET);
+        // Idempotent (the blank lines between the entries aren't kept by the
+        // parser).
+        let formatted = HEADERS_TARGET.replacen("\n\n#", "\n#", 1);
+        assert_eq!(groups(&formatted, Group::SourceWithHeaders), HEADERS_TARGET);
+    }
+
+    #[test]
+    fn group_expanded_only() {
+        const SOURCE: &str = "[dependencies.a]\n\
+            workspace = true\n\
+            features = [\"f\"]\n\
+            \n\
+            [dependencies.b]\n\
+            version = \"1.0.0\"\n\
+            features = [\"f\"]\n";
+        assert_eq!(groups(SOURCE, Group::Source), SOURCE);
+        assert_eq!(groups(SOURCE, Group::SourceWithHeaders), SOURCE);
+    }
+
+    #[test]
+    fn group_stale_headers() {
+        // `a` is added before the first dependency, `b` moved from the path
+        // block to the registry one.
+        const SOURCE: &str = "[dependencies]\n\
+            a = \"1.0.0\"\n\
+            # Path dependencies\n\
+            # comment\n\
+            b = \"1.0.0\"\n\
+            # Registry dependencies\n\
+            c = \"1.0.0\"\n";
+        const TARGET: &str = "[dependencies]\n\
+            # Registry dependencies\n\
+            a = \"1.0.0\"\n\
+            # comment\n\
+            b = \"1.0.0\"\n\
+            c = \"1.0.0\"\n";
+        assert_eq!(groups(SOURCE, Group::SourceWithHeaders), TARGET);
+        assert_eq!(
+            groups(SOURCE, Group::Source),
+            TARGET.replace("# Registry dependencies\n", ""),
+        );
+    }
+
+    #[test]
+    fn dotted_multiline_string() {
+        const SOURCE: &str = "[package]\n\
+            description = \"\"\"'''\"\"\"\n\
+            version = { workspace = true }\n\
+            readme = '''\n\
+            edition = { workspace = true }\n\
+            '''\n";
+        const TARGET: &str = "[package]\n\
+            description = \"\"\"'''\"\"\"\n\
+            version.workspace = true\n\
+            readme = '''\n\
+            edition = { workspace = true }\n\
+            '''\n";
+        assert_eq!(Dotted::new(SOURCE).to_string(), TARGET);
+    }
+
+    // Whether each line of the source is (a part of) a multi-line string.
+    fn multiline_strings(source: &str) -> Vec<bool> {
+        let mut lexer = Lexer::default();
+        source
+            .split('\n')
+            .map(|line| lexer.multiline_string(line))
+            .collect()
+    }
+
+    #[test]
+    fn multiline_string() {
+        // `'''` inside the `"""` string.
+        assert_eq!(
+            multiline_strings("a = \"\"\"'''\"\"\"\nb = 1"),
+            [true, false],
+        );
+        // The string opened by `"""` is closed by `"""` only.
+        assert_eq!(
+            multiline_strings("a = \"\"\"b\n''' \"\"\"\nc = 1"),
+            [true, true, false],
+        );
+        assert_eq!(
+            multiline_strings("a = '''b\n\"\"\" '''\nc = 1"),
+            [true, true, false],
+        );
+        // Escaped quotes and the quotes before the closing delimiter.
+        assert_eq!(
+            multiline_strings("a = \"\"\"\\\"\"\nb\"\"\"\"\nc = 1"),
+            [true, true, false],
+        );
+        // Delimiters inside the single-line strings and the comments.
+        assert_eq!(
+            multiline_strings("a = \"'''\" # '''\nb = '\"\"\"'\nc = 1"),
+            [false, false, false],
+        );
+    }
+
+    #[test]
+    fn comment() {
+        assert_eq!(super::comment("a = \"#\" # b"), Some("# b"));
+        assert_eq!(super::comment("# a"), Some("# a"));
+        assert_eq!(super::comment("a = '#'"), None);
+        assert_eq!(super::comment("a = \"\"\"#\"\"\" # b"), Some("# b"));
+        assert_eq!(super::comment("a = \"\\\"#\""), None);
+    }
+
+    #[test]
+    fn header() {
+        assert_eq!(
+            super::header("[target.'cfg(unix)'.dependencies] # comment"),
+            Some(vec![
+                "target".to_string(),
+                "cfg(unix)".to_string(),
+                "dependencies".to_string(),
+            ]),
+        );
+        assert_eq!(super::header("[[bin]]"), Some(vec!["bin".to_string()]));
+        assert_eq!(super::header("a = [1]"), None);
+    }
+}
@@ -1,14 +1,27 @@
 #[doc(inline)]
 pub use self::{
-    format::Format,
+    cfg::duplicate_targets,
+    diagnostic::{Diagnostic, Level},
+    format::{merge_targets, Format},
     inline::Inline,
+    layout::{Group, Inherited, Layout},
+    lint::{fix, lint, span},
     order::{Order, Ordered},
     settings::Settings,
+    topological::feature_cycles,
+    verify::{verify, Difference},
 };
 
 pub mod settings;
 
+mod categories;
+mod cfg;
+mod diagnostic;
 mod format;
 mod inline;
+mod layout;
+mod lint;
 mod order;
 mod sort;
+mod topological;
+mod verify;
@@ -0,0 +1,537 @@
+use crate::{
+    categories::{closest, CATEGORIES},
+    inline,
+    layout::{array, comment, header, key_value, keys, Lexer},
+    settings::package::Description,
+    Diagnostic, Settings,
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+use toml::Value;
+
+/// Lints the manifest.
+pub fn lint(manifest: &Value, settings: &Settings) -> Vec<Diagnostic> {
+    let mut diagnostics = features(manifest);
+    for &(keys, path) in PACKAGES {
+        let package = keys.iter().try_fold(manifest, |value, key| value.get(key));
+        if let Some(package) = package {
+            diagnostics.extend(description(package, path, &settings.package.description));
+            diagnostics.extend(categories(package, path));
+            diagnostics.extend(keywords(package, path));
+        }
+    }
+    diagnostics
+}
+
+/// Fixes the trivial lint findings in the manifest source: removes the
+/// trailing period of the single-line description of the `[package]` (or the
+/// `[workspace.package]`) table.
+///
+/// Returns the fixed source and the paths of the fixed descriptions.
+pub fn fix(source: &str, settings: &Settings) -> (String, Vec<Vec<String>>) {
+    if !settings.package.description.no_trailing_period {
+        return (source.to_string(), Vec::new());
+    }
+    let mut table = Vec::new();
+    let mut lexer = Lexer::default();
+    let mut lines = Vec::new();
+    let mut paths = Vec::new();
+    for line in source.split('\n') {
+        let trimmed = line.trim_start();
+        if lexer.multiline_string(line) {
+            lines.push(Cow::Borrowed(line));
+            continue;
+        }
+        if let Some(keys) = header(trimmed) {
+            table = keys;
+        } else if let Some((keys, value)) = key_value(trimmed) {
+            let path: Vec<String> = table.iter().chain(&keys).cloned().collect();
+            if PACKAGES.iter().any(|&(package, _)| {
+                let description = package.iter().copied().chain(Some("description"));
+                description.eq(path.iter().map(String::as_str))
+            }) {
+                if let Some(fixed) = trailing_period(value) {
+                    let start = line.len() - value.len();
+                    lines.push(Cow::Owned(format!("{}{}", &line[..start], fixed)));
+                    paths.push(path);
+                    continue;
+                }
+            }
+        }
+        lines.push(Cow::Borrowed(line));
+    }
+    (lines.join("\n"), paths)
+}
+
+/// Finds the span of the diagnostic path (e.g. `features.std.[1]`) in the
+/// manifest source: the array element, the key with its value or the table
+/// header. Falls back to the key of the inline table containing the path.
+pub fn span(source: &str, path: &str) -> Option<Range<usize>> {
+    let (path, index) = match path.strip_suffix(']').and_then(|path| path.rfind(".[")) {
+        Some(end) => (
+            &path[..end],
+            path[end + 2..path.len() - 1].parse::<usize>().ok(),
+        ),
+        None => (path, None),
+    };
+    let path = match keys(path)? {
+        (path, "") => path,
+        _ => return None,
+    };
+    let mut table = Vec::new();
+    let mut lexer = Lexer::default();
+    let mut found = None;
+    let mut offset = 0;
+    for line in source.split('\n') {
+        let start = offset + line.len() - line.trim_start().len();
+        offset += line.len() + 1;
+        let inside = lexer.inside_string();
+        lexer.multiline_string(line);
+        if inside {
+            continue;
+        }
+        let code = code(line.trim_start());
+        if let Some(keys) = header(code) {
+            if keys == path {
+                return Some(start..start + code.len());
+            }
+            table = keys;
+        } else if let Some((keys, value)) = key_value(code) {
+            let keys: Vec<&String> = table.iter().chain(&keys).collect();
+            let end = start + code.len();
+            if keys.iter().copied().eq(&path) {
+                let element = index.and_then(|index| array(value)?.get(index).copied());
+                return Some(match element {
+                    Some(element) => {
+                        let start = end - value.len()
+                            + (element.as_ptr() as usize - value.as_ptr() as usize);
+                        start..start + element.len()
+                    }
+                    None => start..end,
+                });
+            }
+            if found.is_none()
+                && path.len() > keys.len()
+                && keys.iter().copied().eq(&path[..keys.len()])
+            {
+                found = Some(start..end);
+            }
+        }
+    }
+    found
+}
+
+// The line without the comment.
+fn code(line: &str) -> &str {
+    let end = comment(line).map_or(line.len(), |comment| line.len() - comment.len());
+    line[..end].trim_end()
+}
+
+// Paths of the package tables.
+const PACKAGES: &[(&[&str], &str)] = &[
+    (&["package"], "package"),
+    (&["workspace", "package"], "workspace.package"),
+];
+
+// Maximal number of the categories and the keywords accepted by crates.io.
+const MAX_COUNT: usize = 5;
+
+// The single-line string value without the trailing period (if any).
+fn trailing_period(value: &str) -> Option<String> {
+    let string = code(value);
+    let quote = string.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let text = string.strip_suffix(quote)?.strip_prefix(quote)?;
+    let text = text.strip_suffix('.').filter(|text| !text.ends_with('.'))?;
+    Some(format!("{1}{0}{1}{2}", text, quote, &value[string.len()..]))
+}
+
+// Checks the description of the package: starts with a capital letter, has no
+// period at the end, is a single sentence and isn't too long.
+fn description(package: &Value, path: &str, rules: &Description) -> Vec<Diagnostic> {
+    let description = match package.get("description").and_then(Value::as_str) {
+        Some(description) => description.trim(),
+        None => return Vec::new(),
+    };
+    let mut diagnostics = Vec::new();
+    if rules.capital_letter && matches!(description.chars().next(), Some(c) if c.is_lowercase()) {
+        diagnostics.push(Diagnostic::warning(
+            "description doesn't start with a capital letter",
+        ));
+    }
+    if rules.no_trailing_period && description.ends_with('.') && !description.ends_with("..") {
+        diagnostics.push(
+            Diagnostic::warning("description ends with a period")
+                .note("`cargo pretty lint --fix` removes it"),
+        );
+    }
+    if rules.single_sentence && sentences(description) > 1 {
+        diagnostics.push(Diagnostic::warning("description isn't a single sentence"));
+    }
+    if let Some(max_length) = rules.max_length {
+        if description.chars().count() > max_length {
+            diagnostics.push(Diagnostic::warning(format!(
+                "description is longer than {} characters",
+                max_length
+            )));
+        }
+    }
+    let path = format!("{}.description", path);
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| diagnostic.path(path.clone()))
+        .collect()
+}
+
+// Checks the categories of the package: the categories are crates.io category
+// slugs, there are at most five of them.
+fn categories(package: &Value, path: &str) -> Vec<Diagnostic> {
+    let categories = match package.get("categories").and_then(Value::as_array) {
+        Some(categories) => categories,
+        None => return Vec::new(),
+    };
+    let path = format!("{}.categories", path);
+    let mut diagnostics = Vec::new();
+    if categories.len() > MAX_COUNT {
+        diagnostics.push(
+            Diagnostic::warning(format!("more than {} categories", MAX_COUNT))
+                .path(path.clone())
+                .note(format!(
+                    "crates.io accepts at most {} categories",
+                    MAX_COUNT
+                )),
+        );
+    }
+    for (index, category) in categories.iter().enumerate() {
+        let category = match category.as_str() {
+            Some(category) => category,
+            None => continue,
+        };
+        if CATEGORIES.binary_search(&category).is_ok() {
+            continue;
+        }
+        let diagnostic = Diagnostic::warning(format!("unknown category `{}`", category))
+            .path(format!("{}.[{}]", path, index));
+        diagnostics.push(match closest(category) {
+            Some(closest) => diagnostic.note(format!("did you mean `{}`?", closest)),
+            None => diagnostic.note("see https://crates.io/category_slugs"),
+        });
+    }
+    diagnostics
+}
+
+// Checks the keywords of the package: there are at most five of them.
+fn keywords(package: &Value, path: &str) -> Option<Diagnostic> {
+    let keywords = package.get("keywords").and_then(Value::as_array)?;
+    if keywords.len() <= MAX_COUNT {
+        return None;
+    }
+    Some(
+        Diagnostic::warning(format!("more than {} keywords", MAX_COUNT))
+            .path(format!("{}.keywords", path))
+            .note(format!("crates.io accepts at most {} keywords", MAX_COUNT)),
+    )
+}
+
+// Number of the sentences: a sentence ends with `.`, `!` or `?` followed by a
+// whitespace and a capital letter (so `e.g. a` doesn't end a sentence).
+fn sentences(text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let ends = chars.windows(3).filter(|window| {
+        matches!(window[0], '.' | '!' | '?')
+            && window[1].is_whitespace()
+            && window[2].is_uppercase()
+    });
+    ends.count() + 1
+}
+
+// Dependencies of the manifest (the features can refer to) with the optional
+// flag.
+fn dependencies(manifest: &Value) -> HashMap<&str, bool> {
+    let tables = ["dependencies", "dev-dependencies", "build-dependencies"];
+    let targets = manifest
+        .get("target")
+        .and_then(Value::as_table)
+        .into_iter()
+        .flat_map(|targets| targets.values());
+    let mut dependencies = HashMap::new();
+    for table in Some(manifest).into_iter().chain(targets) {
+        for &key in &tables {
+            let table = table
+                .get(key)
+                .and_then(Value::as_table)
+                .into_iter()
+                .flatten();
+            for (name, spec) in table {
+                let optional = spec.get("optional").and_then(Value::as_bool) == Some(true);
+                *dependencies.entry(name.as_str()).or_insert(false) |= optional;
+            }
+        }
+    }
+    dependencies
+}
+
+// Checks the features: the entries refer to the existing features and
+// dependencies, the optional dependencies are referenced, the empty features
+// don't shadow the implicit features of the optional dependencies.
+fn features(manifest: &Value) -> Vec<Diagnostic> {
+    let features = match manifest.get("features").and_then(Value::as_table) {
+        Some(features) => features,
+        None => return Vec::new(),
+    };
+    let dependencies = dependencies(manifest);
+    let optional = |name: &str| dependencies.get(name) == Some(&true);
+    let mut referenced = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for (feature, entries) in features {
+        let path = format!("features.{}", inline::key(feature));
+        let entries = entries.as_array().map(Vec::as_slice).unwrap_or_default();
+        if entries.is_empty() && optional(feature) {
+            diagnostics.push(
+                Diagnostic::warning(format!(
+                    "empty feature `{}` shadows the implicit feature of the optional dependency",
+                    feature
+                ))
+                .path(path.clone())
+                .note(format!(
+                    "use `{0} = [\"dep:{0}\"]` to enable the dependency",
+                    feature
+                )),
+            );
+        }
+        for (index, entry) in entries.iter().enumerate() {
+            let entry = match entry.as_str() {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let path = format!("{}.[{}]", path, index);
+            if let Some(dependency) = entry.strip_prefix("dep:") {
+                referenced.insert(dependency);
+                if !optional(dependency) {
+                    diagnostics.push(
+                        Diagnostic::error(format!("`{}` isn't an optional dependency", dependency))
+                            .path(path),
+                    );
+                }
+            } else if let Some(index) = entry.find('/') {
+                let dependency = &entry[..index];
+                let (dependency, weak) = match dependency.strip_suffix('?') {
+                    Some(dependency) => (dependency, true),
+                    None => (dependency, false),
+                };
+                referenced.insert(dependency);
+                if weak && !optional(dependency) {
+                    diagnostics.push(
+                        Diagnostic::error(format!("`{}` isn't an optional dependency", dependency))
+                            .path(path),
+                    );
+                } else if !dependencies.contains_key(dependency) {
+                    diagnostics.push(
+                        Diagnostic::error(format!("`{}` isn't a dependency", dependency))
+                            .path(path),
+                    );
+                }
+            } else {
+                referenced.insert(entry);
+                if !features.contains_key(entry) && !optional(entry) {
+                    diagnostics
+                        .push(Diagnostic::error(format!("unknown feature `{}`", entry)).path(path));
+                }
+            }
+        }
+    }
+    let mut unreferenced: Vec<_> = dependencies
+        .iter()
+        .filter(|&(name, &optional)| {
+            optional && !referenced.contains(name) && !features.contains_key(*name)
+        })
+        .map(|(name, _)| *name)
+        .collect();
+    unreferenced.sort_unstable();
+    for dependency in unreferenced {
+        diagnostics.push(
+            Diagnostic::warning(format!(
+                "optional dependency `{}` isn't referenced by any feature",
+                dependency
+            ))
+            .path("features")
+            .note("it can only be enabled by its implicit feature (before edition 2024)"),
+        );
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn features() -> Result<()> {
+        const SOURCE: &str = r#"
+            [dependencies]
+            a = { version = "1.0.0", optional = true }
+            b = "1.0.0"
+            c = { version = "1.0.0", optional = true }
+            d = { version = "1.0.0", optional = true }
+
+            [features]
+            default = ["std", "unknown"]
+            std = ["dep:a", "dep:b", "b/std", "e/std", "b?/alloc"]
+            c = []
+        "#;
+        assert_eq!(
+            lint(&toml::from_str(SOURCE)?, &Settings::default()),
+            vec![
+                Diagnostic::error("unknown feature `unknown`").path("features.default.[1]"),
+                Diagnostic::error("`b` isn't an optional dependency").path("features.std.[1]"),
+                Diagnostic::error("`e` isn't a dependency").path("features.std.[3]"),
+                Diagnostic::error("`b` isn't an optional dependency").path("features.std.[4]"),
+                Diagnostic::warning(
+                    "empty feature `c` shadows the implicit feature of the optional dependency"
+                )
+                .path("features.c")
+                .note("use `c = [\"dep:c\"]` to enable the dependency"),
+                Diagnostic::warning("optional dependency `d` isn't referenced by any feature")
+                    .path("features")
+                    .note("it can only be enabled by its implicit feature (before edition 2024)"),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dev_dependencies() -> Result<()> {
+        const SOURCE: &str = r#"
+            [dev-dependencies]
+            a = "1.0.0"
+
+            [target.'cfg(unix)'.dev-dependencies]
+            b = "1.0.0"
+
+            [features]
+            test = ["a/std", "b/std"]
+        "#;
+        assert_eq!(lint(&toml::from_str(SOURCE)?, &Settings::default()), vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn description() -> Result<()> {
+        const SOURCE: &str = r#"
+            [package]
+            description = "a formatter. It formats the manifest."
+        "#;
+        let mut settings = Settings::default();
+        settings.package.description.max_length = Some(20);
+        assert_eq!(
+            lint(&toml::from_str(SOURCE)?, &settings),
+            vec![
+                Diagnostic::warning("description doesn't start with a capital letter")
+                    .path("package.description"),
+                Diagnostic::warning("description ends with a period")
+                    .path("package.description")
+                    .note("`cargo pretty lint --fix` removes it"),
+                Diagnostic::warning("description isn't a single sentence")
+                    .path("package.description"),
+                Diagnostic::warning("description is longer than 20 characters")
+                    .path("package.description"),
+            ],
+        );
+        assert_eq!(
+            lint(
+                &toml::from_str(r#"package.description = "Formats e.g. the manifest...""#)?,
+                &Settings::default(),
+            ),
+            vec![],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn categories() -> Result<()> {
+        const SOURCE: &str = r#"
+            [package]
+            categories = ["command-line-utility", "config", "foo"]
+            keywords = ["a", "b", "c", "d", "e", "f"]
+        "#;
+        assert_eq!(
+            lint(&toml::from_str(SOURCE)?, &Settings::default()),
+            vec![
+                Diagnostic::warning("unknown category `command-line-utility`")
+                    .path("package.categories.[0]")
+                    .note("did you mean `command-line-utilities`?"),
+                Diagnostic::warning("unknown category `foo`")
+                    .path("package.categories.[2]")
+                    .note("see https://crates.io/category_slugs"),
+                Diagnostic::warning("more than 5 keywords")
+                    .path("package.keywords")
+                    .note("crates.io accepts at most 5 keywords"),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn span() {
+        const SOURCE: &str = r#"[package]
+description = """
+[features]
+"""
+keywords = ["a", "b"] # comment
+
+[features]
+default = ["std", "unknown"]
+std = [
+    "alloc",
+]
+"#;
+        let span = |path| super::span(SOURCE, path).map(|span| &SOURCE[span]);
+        assert_eq!(span("package.keywords"), Some(r#"keywords = ["a", "b"]"#));
+        assert_eq!(span("package.keywords.[1]"), Some(r#""b""#));
+        assert_eq!(span("features"), Some("[features]"));
+        assert_eq!(span("features.default.[1]"), Some(r#""unknown""#));
+        assert_eq!(span("features.std.[0]"), Some("std = ["));
+        assert_eq!(span("features.c"), None);
+        assert_eq!(
+            super::span("package = { description = \"a.\" }", "package.description"),
+            Some(0..32),
+        );
+    }
+
+    #[test]
+    fn fix() {
+        const SOURCE: &str = r#"
+[package]
+description = "Formats the manifest." # comment
+[workspace.package]
+description = 'Formats the manifest.'
+[package.metadata]
+description = "Formats the manifest."
+"#;
+        assert_eq!(
+            super::fix(SOURCE, &Settings::default()),
+            (
+                r#"
+[package]
+description = "Formats the manifest" # comment
+[workspace.package]
+description = 'Formats the manifest'
+[package.metadata]
+description = "Formats the manifest."
+"#
+                .to_string(),
+                vec![
+                    vec!["package".to_string(), "description".to_string()],
+                    vec![
+                        "workspace".to_string(),
+                        "package".to_string(),
+                        "description".to_string(),
+                    ],
+                ],
+            ),
+        );
+    }
+}
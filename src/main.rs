@@ -4,15 +4,15 @@
 // - serde_diff:
 //      https://github.com/amethyst/serde-diff/pull/17.
 //      https://github.com/amethyst/serde-diff/issues/6
-// - Inline Auto
-//   если `key.len() + " = ".len() + inline_value.len()` <= max line len
-// - package.description
 // - подлежащее сказуемое (format, config show --path --toml)
 
-use self::options::{Options, SubCommand};
+use self::{
+    commands::Status,
+    options::{Options, SubCommand},
+};
 use anyhow::Result;
-
 use log::{info, trace};
+use std::process::exit;
 
 // set RUST_LOG=info
 fn main() -> Result<()> {
@@ -20,12 +20,21 @@ fn main() -> Result<()> {
     let options = Options::new();
     trace!("options: {:?}", options);
     let Options::Pretty(pretty) = &options;
-    match &pretty.subcommand {
+    let status = match &pretty.subcommand {
         None => commands::pretty(&pretty)?,
-        Some(SubCommand::Config(config)) => commands::pretty::config(&config)?,
+        Some(SubCommand::Config(config)) => {
+            commands::pretty::config(&config)?;
+            Status::Success
+        }
+        Some(SubCommand::Lint(lint)) => commands::pretty::lint(&lint)?,
+    };
+    trace!("status: {:?}", status);
+    if status != Status::Success {
+        exit(status.code());
     }
     Ok(())
 }
 
 mod commands;
+mod diff;
 mod options;
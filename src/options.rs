@@ -1,5 +1,6 @@
-use self::config::Config;
+use self::{config::Config, lint::Lint};
 use anyhow::{bail, Error, Result};
+use atty::Stream;
 use clap::{AppSettings, Clap};
 use std::{convert::TryFrom, ffi::OsStr, path::PathBuf, str::FromStr};
 
@@ -20,7 +21,7 @@ impl Options {
     pub fn new() -> Options {
         let options = Options::parse();
         let Options::Pretty(pretty) = &options;
-        if pretty.subcommand.is_none() {
+        if pretty.subcommand.is_none() && !pretty.stdin {
             for file in &pretty.files {
                 assert!(file.is_file(), "isn't file");
                 assert!(
@@ -35,15 +36,41 @@ impl Options {
 
 #[derive(Clap, Debug)]
 pub struct Pretty {
-    /// Recursively searches the path for the config file.
-    #[clap(name = "CONFIG_PATH", long = "config-path", default_value = "./")]
-    pub config_path: PathBuf,
+    /// Recursively searches the path for the config file (instead of the directory of each manifest file).
+    #[clap(name = "CONFIG_PATH", long = "config-path", parse(from_os_str))]
+    pub config_path: Option<PathBuf>,
     /// Output type.
-    #[clap(name = "OUTPUT", long = "output", default_value = "stdout", possible_values = &["file", "stdout"])]
+    #[clap(name = "OUTPUT", long = "output", default_value = "stdout", possible_values = &["diff", "file", "stdout"])]
     pub output: Output,
+    /// Coloring of the diff output.
+    #[clap(name = "COLOR", long = "color", default_value = "auto", possible_values = &["always", "auto", "never"])]
+    pub color: Color,
     /// Backup any modified files.
     #[clap(short, long = "backup")]
     pub backup: bool,
+    /// Checks that the manifest files are formatted without modifying them.
+    #[clap(long = "check")]
+    pub check: bool,
+    /// Formats the workspace root manifest files and all their member manifest files.
+    #[clap(long = "workspace")]
+    pub workspace: bool,
+    /// Doesn't verify that the formatted manifest has the same meaning as the source one.
+    #[clap(long = "no-verify")]
+    pub no_verify: bool,
+    /// Reads the manifest from stdin and writes the formatted manifest to stdout.
+    #[clap(
+        long = "stdin",
+        conflicts_with_all = &["OUTPUT", "backup", "check", "workspace", "FILES"]
+    )]
+    pub stdin: bool,
+    /// The path of the manifest read from stdin to search for the config file from.
+    #[clap(
+        name = "STDIN_FILEPATH",
+        long = "stdin-filepath",
+        requires = "stdin",
+        parse(from_os_str)
+    )]
+    pub stdin_filepath: Option<PathBuf>,
     /// Sets the manifest files to format.
     #[clap(name = "FILES", default_value = "Cargo.toml", parse(from_os_str))]
     pub files: Vec<PathBuf>,
@@ -55,6 +82,7 @@ pub struct Pretty {
 /// Output type.
 #[derive(Debug)]
 pub enum Output {
+    Diff,
     File,
     Stdout,
 }
@@ -64,6 +92,7 @@ impl TryFrom<&str> for Output {
 
     fn try_from(from: &str) -> Result<Self, Self::Error> {
         match from {
+            "diff" => Ok(Self::Diff),
             "file" => Ok(Self::File),
             "stdout" => Ok(Self::Stdout),
             _ => bail!("can't parse output from the string: {}", from),
@@ -79,10 +108,73 @@ impl FromStr for Output {
     }
 }
 
+/// Coloring.
+#[derive(Clone, Copy, Debug)]
+pub enum Color {
+    Always,
+    Auto,
+    Never,
+}
+
+impl Color {
+    pub fn enabled(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Auto => atty::is(Stream::Stdout),
+            Self::Never => false,
+        }
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = Error;
+
+    fn try_from(from: &str) -> Result<Self, Self::Error> {
+        match from {
+            "always" => Ok(Self::Always),
+            "auto" => Ok(Self::Auto),
+            "never" => Ok(Self::Never),
+            _ => bail!("can't parse color from the string: {}", from),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    fn from_str(from: &str) -> Result<Self, Self::Err> {
+        Self::try_from(from)
+    }
+}
+
 #[derive(Clap, Debug)]
 pub enum SubCommand {
     #[clap(version, author)]
     Config(Config),
+    #[clap(version, author)]
+    Lint(Lint),
+}
+
+pub mod lint {
+    use clap::Clap;
+    use std::path::PathBuf;
+
+    /// Lint manifest.
+    #[derive(Clap, Debug)]
+    pub struct Lint {
+        /// Recursively searches the path for the config file (instead of the directory of each manifest file).
+        #[clap(name = "CONFIG_PATH", long = "config-path", parse(from_os_str))]
+        pub config_path: Option<PathBuf>,
+        /// Fixes the trivial findings in the manifest files.
+        #[clap(long = "fix")]
+        pub fix: bool,
+        /// Backup the fixed files.
+        #[clap(short, long = "backup")]
+        pub backup: bool,
+        /// Sets the manifest files to lint.
+        #[clap(name = "FILES", default_value = "Cargo.toml", parse(from_os_str))]
+        pub files: Vec<PathBuf>,
+    }
 }
 
 pub mod config {
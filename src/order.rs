@@ -46,11 +46,23 @@ impl Unordered {
 }
 
 /// Ordered.
+///
+/// - `Ordered::Alphabetic` => alphabetic order,
+/// - `Ordered::Feature` => local features first, then `dep:` activations, then
+///   `crate/feature` and `crate?/feature` entries, each alphabetically,
+/// - `Ordered::Topological` => `default` first, then each feature after the
+///   features it enables, ties alphabetically (for the features table, any
+///   other table and the arrays are in alphabetic order),
+/// - `Ordered::Enumeration` => enumeration order or else alphabetic order.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, SerdeDiff, Serialize)]
 #[serde(untagged)]
 pub enum Ordered {
     #[serde(with = "Alphabetic")]
     Alphabetic,
+    #[serde(with = "Feature")]
+    Feature,
+    #[serde(with = "Topological")]
+    Topological,
     Enumeration(#[serde_diff(opaque)] IndexSet<String>),
 }
 
@@ -70,3 +82,37 @@ impl Alphabetic {
         Serialize::serialize(&Self::Alphabetic, serializer)
     }
 }
+
+/// Feature.
+#[derive(Deserialize, Serialize)]
+enum Feature {
+    Feature,
+}
+
+impl Feature {
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+        <Self as Deserialize>::deserialize(deserializer)?;
+        Ok(())
+    }
+
+    fn serialize<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&Self::Feature, serializer)
+    }
+}
+
+/// Topological.
+#[derive(Deserialize, Serialize)]
+enum Topological {
+    Topological,
+}
+
+impl Topological {
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+        <Self as Deserialize>::deserialize(deserializer)?;
+        Ok(())
+    }
+
+    fn serialize<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&Self::Topological, serializer)
+    }
+}
@@ -3,6 +3,7 @@ pub use self::{
     badges::Badges,
     dependency_tables::{BuildDependencies, Dependencies, DevDependencies, Targets},
     features::Features,
+    lints::Lints,
     package::Package,
     patch::Patch,
     profile::Profiles,
@@ -11,12 +12,17 @@ pub use self::{
     workspace::Workspace,
 };
 
-use crate::{inline::Inline, order::Order};
+use crate::{
+    inline::Inline,
+    layout::{Group, Inherited, Layout},
+    order::Order,
+};
 use derivative::Derivative;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use serde_diff::SerdeDiff;
-use std::iter::FromIterator;
+use std::{iter::FromIterator, path::PathBuf};
+use toml::Value;
 
 lazy_static! {
     static ref MANIFEST: Order = Order::from_iter(vec![
@@ -32,6 +38,7 @@ lazy_static! {
         "target",
         "badges",
         "features",
+        "lints",
         "replace",
         "patch",
         "replace",
@@ -43,6 +50,7 @@ lazy_static! {
         "version",
         "authors",
         "edition",
+        "rust-version",
         "description",
         "documentation",
         "readme",
@@ -60,10 +68,12 @@ lazy_static! {
         "publish",
         "metadata",
         "default-run",
+        "autolib",
         "autobins",
         "autoexamples",
         "autotests",
         "autobenches",
+        "resolver",
     ]);
     static ref TARGETS: Order = Order::from_iter(vec!["lib", "bin", "example", "test", "bench"]);
     static ref TARGET: Order = Order::from_iter(vec![
@@ -86,6 +96,7 @@ lazy_static! {
         "build-dependencies",
     ]);
     static ref DEPENDENCY: Order = Order::from_iter(vec![
+        "workspace",
         "version",
         "git",
         "branch",
@@ -121,6 +132,7 @@ lazy_static! {
         "build",
     ]);
     static ref FEATURES: Order = Order::from_iter(vec!["default"]);
+    static ref LINTS: Order = Order::from_iter(vec!["rust", "rustdoc", "clippy"]);
     static ref PROFILES: Order = Order::from_iter(vec!["dev", "release", "test", "bench"]);
     static ref PROFILE: Order = Order::from_iter(vec![
         "opt-level",
@@ -133,7 +145,15 @@ lazy_static! {
         "codegen-units",
         "rpath",
     ]);
-    static ref WORKSPACE: Order = Order::from_iter(vec!["members", "default-members", "exclude"]);
+    static ref WORKSPACE: Order = Order::from_iter(vec![
+        "members",
+        "default-members",
+        "exclude",
+        "package",
+        "dependencies",
+        "lints",
+        "metadata",
+    ]);
 }
 
 /// Settings.
@@ -143,6 +163,12 @@ lazy_static! {
 pub struct Settings {
     #[derivative(Default(value = "MANIFEST.clone()"))]
     pub order: Order,
+    #[derivative(Default(value = "100"))]
+    pub max_width: usize,
+    pub inherited: Inherited,
+    #[serde_diff(opaque)]
+    pub extends: Option<PathBuf>,
+    pub inherit: bool,
     pub package: Package,
     pub lib: Lib,
     pub bin: Bin,
@@ -155,12 +181,31 @@ pub struct Settings {
     pub targets: Targets,
     pub badges: Badges,
     pub features: Features,
+    pub lints: Lints,
     pub patch: Patch,
     pub replace: Replace,
     pub profiles: Profiles,
     pub workspace: Workspace,
 }
 
+/// Merges the overrides into the base config. Tables are merged recursively,
+/// any other value is replaced.
+pub fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Table(base), Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(base) => merge(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
 pub mod package {
     use super::*;
 
@@ -174,6 +219,7 @@ pub mod package {
         #[derivative(Default(value = "Inline::Manual(Some(1))"))]
         pub inline: Inline,
         pub authors: Authors,
+        pub description: Description,
         pub keywords: Keywords,
         pub categories: Categories,
         pub exclude: Exclude,
@@ -183,37 +229,66 @@ pub mod package {
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Authors {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
+    /// The description rules (checked by the lint).
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+    pub struct Description {
+        #[derivative(Default(value = "true"))]
+        pub capital_letter: bool,
+        #[derivative(Default(value = "true"))]
+        pub no_trailing_period: bool,
+        #[derivative(Default(value = "true"))]
+        pub single_sentence: bool,
+        pub max_length: Option<usize>,
+    }
+
+    #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
+    #[derivative(Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Keywords {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Categories {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Exclude {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Include {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
@@ -283,16 +358,22 @@ pub mod target_tables {
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct CrateType {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct RequiredFeatures {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 }
 
@@ -308,6 +389,7 @@ pub mod dependency_tables {
         pub order: Order,
         #[derivative(Default(value = "Inline::Manual(Some(1))"))]
         pub inline: Inline,
+        pub group: Group,
         #[serde(rename = "*")]
         pub dependency: Dependency,
     }
@@ -320,6 +402,7 @@ pub mod dependency_tables {
         pub order: Order,
         #[derivative(Default(value = "Inline::Manual(Some(1))"))]
         pub inline: Inline,
+        pub group: Group,
         #[serde(rename = "*")]
         pub dependency: Dependency,
     }
@@ -332,6 +415,7 @@ pub mod dependency_tables {
         pub order: Order,
         #[derivative(Default(value = "Inline::Manual(Some(1))"))]
         pub inline: Inline,
+        pub group: Group,
         #[serde(rename = "*")]
         pub dependency: Dependency,
     }
@@ -342,6 +426,8 @@ pub mod dependency_tables {
     #[serde(default, deny_unknown_fields)]
     pub struct Targets {
         pub order: Order,
+        pub canonicalize: bool,
+        pub merge: bool,
         pub dependencies: Dependencies,
         pub dev_dependencies: DevDependencies,
         pub build_dependencies: BuildDependencies,
@@ -411,9 +497,58 @@ pub mod features {
     /// Any feature.
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Feature {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
+    }
+}
+
+pub mod lints {
+    use super::*;
+
+    /// The lints settings.
+    #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
+    #[derivative(Default)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Lints {
+        #[derivative(Default(value = "LINTS.clone()"))]
+        pub order: Order,
+        #[derivative(Default(value = "Inline::Manual(Some(2))"))]
+        pub inline: Inline,
+        #[serde(rename = "*")]
+        pub tool: Tool,
+    }
+
+    /// Any tool.
+    #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
+    #[derivative(Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+    pub struct Tool {
+        pub order: Order,
+        pub sort_by: SortBy,
+    }
+
+    /// Lint sort key.
+    ///
+    /// - `SortBy::Name` => by the order only,
+    /// - `SortBy::Level` => by the level (`forbid`, `deny`, `warn`, `allow`),
+    ///   then by the order,
+    /// - `SortBy::Priority` => by the priority (lowest first), then by the
+    ///   order.
+    #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, SerdeDiff, Serialize)]
+    pub enum SortBy {
+        Name,
+        Level,
+        Priority,
+    }
+
+    impl Default for SortBy {
+        fn default() -> Self {
+            Self::Name
+        }
     }
 }
 
@@ -482,27 +617,56 @@ pub mod workspace {
         pub members: Members,
         pub default_members: DefaultMembers,
         pub exclude: Exclude,
+        pub package: Package,
+        pub dependencies: Dependencies,
+        pub lints: Lints,
+        pub metadata: package::Metadata,
     }
 
+    /// The workspace package settings.
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
     #[serde(default, deny_unknown_fields)]
+    pub struct Package {
+        #[derivative(Default(value = "PACKAGE.clone()"))]
+        pub order: Order,
+        #[derivative(Default(value = "Inline::Manual(Some(1))"))]
+        pub inline: Inline,
+        pub authors: package::Authors,
+        pub keywords: package::Keywords,
+        pub categories: package::Categories,
+        pub exclude: package::Exclude,
+        pub include: package::Include,
+    }
+
+    #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
+    #[derivative(Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Members {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct DefaultMembers {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 
     #[derive(Clone, Debug, Derivative, Deserialize, PartialEq, SerdeDiff, Serialize)]
     #[derivative(Default)]
-    #[serde(default, deny_unknown_fields)]
+    #[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
     pub struct Exclude {
         pub order: Order,
+        pub layout: Layout,
+        #[derivative(Default(value = "true"))]
+        pub trailing_comma: bool,
     }
 }
 
@@ -530,6 +694,51 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn extends() -> Result<()> {
+        const CONFIG: &str = r#"
+            extends = "../manifestfmt.toml"
+        "#;
+        let settings: Settings = toml::from_str(CONFIG)?;
+        assert_eq!(settings.extends, Some(PathBuf::from("../manifestfmt.toml")));
+        Ok(())
+    }
+
+    #[test]
+    fn merge() -> Result<()> {
+        const PARENT: &str = r#"
+            max-width = 80
+
+            [package]
+            order = "Alphabetic"
+            inline = 0
+
+            [features]
+            order = "Alphabetic"
+        "#;
+        const CHILD: &str = r#"
+            inherit = true
+
+            [package]
+            inline = "None"
+
+            [features]
+            order = ["default", "std"]
+        "#;
+        let mut value: Value = toml::from_str(PARENT)?;
+        super::merge(&mut value, toml::from_str(CHILD)?);
+        let settings: Settings = value.try_into()?;
+        assert_eq!(settings.max_width, 80);
+        assert!(settings.inherit);
+        assert_eq!(settings.package.order, Order::Ordered(Ordered::Alphabetic));
+        assert_eq!(settings.package.inline, Inline::Manual(None));
+        assert_eq!(
+            settings.features.order,
+            Order::from_iter(vec!["default", "std"]),
+        );
+        Ok(())
+    }
+
     #[test]
     fn order() -> Result<()> {
         const CONFIG: &str = r#"
@@ -541,6 +750,33 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn order_feature() -> Result<()> {
+        const CONFIG: &str = r#"
+            [features."*"]
+            order = "Feature"
+        "#;
+        let settings: Settings = toml::from_str(CONFIG)?;
+        assert_eq!(
+            settings.features.feature.order,
+            Order::Ordered(Ordered::Feature),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn layout() -> Result<()> {
+        const CONFIG: &str = r#"
+            [package.keywords]
+            layout = "Vertical"
+            trailing-comma = false
+        "#;
+        let settings: Settings = toml::from_str(CONFIG)?;
+        assert_eq!(settings.package.keywords.layout, Layout::Vertical);
+        assert!(!settings.package.keywords.trailing_comma);
+        Ok(())
+    }
+
     #[test]
     fn inline_auto() -> Result<()> {
         const CONFIG: &str = r#"
@@ -552,6 +788,16 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn max_width() -> Result<()> {
+        const CONFIG: &str = r#"
+            max-width = 80
+        "#;
+        let settings: Settings = toml::from_str(CONFIG)?;
+        assert_eq!(settings.max_width, 80);
+        Ok(())
+    }
+
     #[test]
     fn inline_none() -> Result<()> {
         const CONFIG: &str = r#"
@@ -1,11 +1,15 @@
-use crate::order::{Order, Ordered};
-use std::cmp::Ordering;
+use crate::{
+    order::{Order, Ordered},
+    topological::{enabled, topological},
+};
+use std::{cmp::Ordering, collections::HashMap};
 use toml_lalrpop::value::{Array, Item, Table, Value};
 
 fn cmp(order: &Order, a: &str, b: &str) -> Ordering {
     match order {
         Order::Unordered => Ordering::Equal,
-        Order::Ordered(Ordered::Alphabetic) => a.cmp(b),
+        Order::Ordered(Ordered::Alphabetic) | Order::Ordered(Ordered::Topological) => a.cmp(b),
+        Order::Ordered(Ordered::Feature) => feature(a).cmp(&feature(b)),
         Order::Ordered(Ordered::Enumeration(map)) => match (map.get_full(a), map.get_full(b)) {
             (None, None) => a.cmp(b),
             (Some(a), Some(ref b)) => a.cmp(b),
@@ -14,6 +18,25 @@ fn cmp(order: &Order, a: &str, b: &str) -> Ordering {
     }
 }
 
+// Sort key of the feature array entry (with or without quotes): local
+// features, then `dep:` activations, then `crate/feature` and weak
+// `crate?/feature` entries.
+fn feature(entry: &str) -> (usize, String, bool) {
+    let entry = entry.trim_matches(|c| c == '"' || c == '\'');
+    if let Some(dependency) = entry.strip_prefix("dep:") {
+        return (1, dependency.to_string(), false);
+    }
+    match entry.find('/') {
+        Some(index) => {
+            let (dependency, feature) = entry.split_at(index);
+            let weak = dependency.ends_with('?');
+            let dependency = dependency.trim_end_matches('?');
+            (2, format!("{}{}", dependency, feature), weak)
+        }
+        None => (0, entry.to_string(), false),
+    }
+}
+
 /// Sort.
 pub trait Sort {
     fn sort(&mut self, order: &Order);
@@ -68,8 +91,43 @@ impl Sort for Array {
     }
 }
 
+// Topological order places `default` first, then each feature after the
+// features it enables.
 impl Sort for Table {
     fn sort(&mut self, order: &Order) {
+        if let Order::Ordered(Ordered::Topological) = order {
+            let nodes: Vec<&str> = self
+                .keys()
+                .map(String::as_str)
+                .filter(|&key| key != "default")
+                .collect();
+            let (order, _) = topological(&nodes, |feature| {
+                match self.get(feature).map(|item| &item.value) {
+                    Some(Value::Array(array)) => array
+                        .iter()
+                        .filter_map(|item| match &item.value {
+                            Value::Primitive(primitive) => Some(primitive.to_string()),
+                            _ => None,
+                        })
+                        .filter_map(|entry| {
+                            enabled(&entry).and_then(|feature| self.get_key_value(feature))
+                        })
+                        .map(|(feature, _)| feature.as_str())
+                        .collect(),
+                    _ => Vec::new(),
+                }
+            });
+            let positions: HashMap<String, usize> = order
+                .into_iter()
+                .enumerate()
+                .map(|(position, key)| (key.to_string(), position + 1))
+                .collect();
+            self.sort_by(|a, _, b, _| {
+                let position = |key: &str| positions.get(key).copied().unwrap_or(0);
+                position(a).cmp(&position(b))
+            });
+            return;
+        }
         self.sort_by(|a, _, b, _| cmp(order, a, b));
     }
 }
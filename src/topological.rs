@@ -0,0 +1,136 @@
+use crate::Diagnostic;
+use itertools::Itertools;
+use std::collections::BTreeSet;
+use toml::Value;
+
+/// Topological order of the nodes: a node goes after the nodes it depends on
+/// (ties are broken alphabetically). The nodes on a cycle (and the nodes
+/// depending on them) go last alphabetically.
+///
+/// Returns the order and the nodes on a cycle.
+pub(crate) fn topological<'a>(
+    nodes: &[&'a str],
+    dependencies: impl Fn(&str) -> Vec<&'a str>,
+) -> (Vec<&'a str>, Vec<&'a str>) {
+    let mut remaining: BTreeSet<&str> = nodes.iter().copied().collect();
+    let mut order = Vec::with_capacity(remaining.len());
+    while let Some(node) = remaining.iter().copied().find(|node| {
+        dependencies(node)
+            .iter()
+            .all(|dependency| !remaining.contains(dependency))
+    }) {
+        remaining.remove(node);
+        order.push(node);
+    }
+    // Nodes reachable from themselves through the remaining nodes.
+    let cycle = remaining
+        .iter()
+        .copied()
+        .filter(|&node| {
+            let mut visited = BTreeSet::new();
+            let mut stack = dependencies(node);
+            while let Some(dependency) = stack.pop() {
+                if dependency == node {
+                    return true;
+                }
+                if remaining.contains(dependency) && visited.insert(dependency) {
+                    stack.extend(dependencies(dependency));
+                }
+            }
+            false
+        })
+        .collect();
+    order.extend(remaining);
+    (order, cycle)
+}
+
+/// Local features enabled by the feature (without `dep:` activations and
+/// `crate/feature` entries).
+pub(crate) fn enabled(entry: &str) -> Option<&str> {
+    let entry = entry.trim_matches(|c| c == '"' || c == '\'');
+    if entry.starts_with("dep:") || entry.contains('/') {
+        None
+    } else {
+        Some(entry)
+    }
+}
+
+/// Reports the cycles between the features of the manifest.
+pub fn feature_cycles(manifest: &Value) -> Vec<Diagnostic> {
+    let features = match manifest.get("features").and_then(Value::as_table) {
+        Some(features) => features,
+        None => return Vec::new(),
+    };
+    let nodes: Vec<&str> = features.keys().map(String::as_str).collect();
+    let (_, cycle) = topological(&nodes, |feature| {
+        features
+            .get(feature)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .filter_map(enabled)
+            .collect()
+    });
+    if cycle.is_empty() {
+        return Vec::new();
+    }
+    vec![Diagnostic::warning(format!(
+        "features {} enable each other",
+        cycle
+            .iter()
+            .format_with(", ", |feature, f| f(&format_args!("`{}`", feature)))
+    ))
+    .path("features")
+    .note("cargo rejects cyclic features")]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn order() {
+        let dependencies = |node: &str| match node {
+            "a" => vec!["c"],
+            "b" => vec!["a", "d"],
+            _ => vec![],
+        };
+        assert_eq!(
+            topological(&["a", "b", "c", "d"], dependencies),
+            (vec!["c", "a", "d", "b"], vec![]),
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let dependencies = |node: &str| match node {
+            "a" => vec!["b"],
+            "b" => vec!["a"],
+            "c" => vec!["a"],
+            _ => vec![],
+        };
+        assert_eq!(
+            topological(&["a", "b", "c", "d"], dependencies),
+            (vec!["d", "a", "b", "c"], vec!["a", "b"]),
+        );
+    }
+
+    #[test]
+    fn feature_cycles() -> Result<()> {
+        const SOURCE: &str = r#"
+            [features]
+            a = ["b", "dep:c"]
+            b = ["a", "c/d"]
+            e = []
+        "#;
+        assert_eq!(
+            super::feature_cycles(&toml::from_str(SOURCE)?),
+            vec![Diagnostic::warning("features `a`, `b` enable each other")
+                .path("features")
+                .note("cargo rejects cyclic features")],
+        );
+        Ok(())
+    }
+}
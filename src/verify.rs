@@ -0,0 +1,293 @@
+use crate::{cfg, format, inline, order::Order, settings::Settings};
+use std::{
+    fmt::{self, Display, Formatter},
+    mem,
+};
+use toml::{de::Error, Value};
+
+/// Difference between the source and the target manifests.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Difference {
+    Added(String),
+    Removed(String),
+    Changed(String),
+    Conflict(String),
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Added(path) => write!(f, "`{}` is added", path),
+            Self::Removed(path) => write!(f, "`{}` is removed", path),
+            Self::Changed(path) => write!(f, "`{}` is changed", path),
+            Self::Conflict(path) => write!(f, "`{}` is specified differently", path),
+        }
+    }
+}
+
+/// Verifies that the target manifest has the same meaning as the source one.
+///
+/// Returns the differences between the parsed manifests (empty if they are
+/// equivalent). The targets are canonicalized and merged as the settings do,
+/// so semantically identical targets (e.g. `cfg(unix)` and `cfg( unix )`) are
+/// the same target. The arrays sorted by the settings are compared regardless
+/// of the order of their elements.
+pub fn verify(source: &str, target: &str, settings: &Settings) -> Result<Vec<Difference>, Error> {
+    let mut source: Value = toml::from_str(source)?;
+    let mut target: Value = toml::from_str(target)?;
+    let mut differences = Vec::new();
+    normalize(&mut source, settings, &mut differences);
+    normalize(&mut target, settings, &mut differences);
+    let mut comparison = Comparison {
+        settings,
+        path: Vec::new(),
+        keys: Vec::new(),
+        differences: &mut differences,
+    };
+    comparison.compare(&source, &target);
+    Ok(differences)
+}
+
+// Merges the semantically identical targets (`targets.merge`) and renames the
+// targets to their canonical key unless it's shared (`targets.canonicalize`),
+// as the formatting does. The conflicting values of the merged targets are
+// reported.
+fn normalize(manifest: &mut Value, settings: &Settings, differences: &mut Vec<Difference>) {
+    let targets = match manifest.get_mut("target") {
+        Some(Value::Table(targets)) => targets,
+        _ => return,
+    };
+    if settings.targets.merge {
+        let (merged, conflicts) = cfg::merge(mem::take(targets).into_iter().collect());
+        targets.extend(merged);
+        differences.extend(
+            conflicts
+                .iter()
+                .map(|keys| Difference::Conflict(cfg::path(keys))),
+        );
+    }
+    if settings.targets.canonicalize {
+        let keys: Vec<&str> = targets.keys().map(String::as_str).collect();
+        let keys = cfg::canonical_keys(&keys);
+        let values = mem::take(targets).into_iter().map(|(_, value)| value);
+        targets.extend(keys.into_iter().zip(values));
+    }
+}
+
+// Comparison of the source and the target manifests.
+struct Comparison<'a> {
+    settings: &'a Settings,
+    // Displayed path.
+    path: Vec<String>,
+    // Keys of the path (without the indices of the arrays of tables).
+    keys: Vec<String>,
+    differences: &'a mut Vec<Difference>,
+}
+
+impl Comparison<'_> {
+    fn compare(&mut self, source: &Value, target: &Value) {
+        match (source, target) {
+            (Value::Table(source), Value::Table(target)) => {
+                for (key, source) in source {
+                    self.push(key);
+                    match target.get(key) {
+                        Some(target) => self.compare(source, target),
+                        None => self.difference(Difference::Removed),
+                    }
+                    self.pop();
+                }
+                for key in target.keys().filter(|key| !source.contains_key(*key)) {
+                    self.push(key);
+                    self.difference(Difference::Added);
+                    self.pop();
+                }
+            }
+            (Value::Array(source), Value::Array(target)) if source.len() == target.len() => {
+                if self.reorderable() {
+                    let (source, target) = (elements(source), elements(target));
+                    if source != target {
+                        self.difference(Difference::Changed);
+                    }
+                    return;
+                }
+                for (index, (source, target)) in source.iter().zip(target).enumerate() {
+                    self.path.push(format!("[{}]", index));
+                    self.compare(source, target);
+                    self.path.pop();
+                }
+            }
+            (Value::Float(source), Value::Float(target)) if source.is_nan() && target.is_nan() => {}
+            (source, target) if source == target => {}
+            _ => self.difference(Difference::Changed),
+        }
+    }
+
+    // Whether the settings sort the array at the path.
+    fn reorderable(&self) -> bool {
+        let keys: Vec<&str> = self.keys.iter().map(String::as_str).collect();
+        !matches!(
+            format::array(self.settings, &keys),
+            None | Some(format::Array {
+                order: Order::Unordered,
+                ..
+            })
+        )
+    }
+
+    fn push(&mut self, key: &str) {
+        self.path.push(inline::key(key).into_owned());
+        self.keys.push(key.to_owned());
+    }
+
+    fn pop(&mut self) {
+        self.path.pop();
+        self.keys.pop();
+    }
+
+    fn difference(&mut self, difference: impl FnOnce(String) -> Difference) {
+        self.differences.push(difference(self.path.join(".")));
+    }
+}
+
+// Elements of the array in the order of their displayed values.
+fn elements(array: &[Value]) -> Vec<String> {
+    let mut elements: Vec<String> = array.iter().map(ToString::to_string).collect();
+    elements.sort();
+    elements
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::order::Ordered;
+    use anyhow::Result;
+
+    #[test]
+    fn equivalent() -> Result<()> {
+        const SOURCE: &str = r#"
+            [dependencies.a]
+            version = "1.0.0"
+            features = ["b", "c"]
+        "#;
+        const TARGET: &str = r#"
+            [dependencies]
+            a = { version = "1.0.0", features = ["b", "c"] }
+        "#;
+        assert_eq!(verify(SOURCE, TARGET, &Settings::default())?, Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn targets() -> Result<()> {
+        const SOURCE: &str = r#"
+            [target.'cfg(all(unix,not(target_os="macos")))'.dependencies]
+            a = "1.0.0"
+        "#;
+        const TARGET: &str = r#"
+            [target.'cfg(all(not(target_os = "macos"), unix))'.dependencies]
+            a = "1.0.0"
+        "#;
+        let mut settings = Settings::default();
+        assert_eq!(
+            verify(SOURCE, TARGET, &settings)?,
+            vec![
+                Difference::Removed(
+                    r#"target."cfg(all(unix,not(target_os=\"macos\")))""#.to_string()
+                ),
+                Difference::Added(
+                    r#"target."cfg(all(not(target_os = \"macos\"), unix))""#.to_string()
+                ),
+            ],
+        );
+        settings.targets.canonicalize = true;
+        assert_eq!(verify(SOURCE, TARGET, &settings)?, Vec::new());
+        Ok(())
+    }
+
+    #[test]
+    fn merged_targets() -> Result<()> {
+        const SOURCE: &str = r#"
+            [target.'cfg(unix)'.dependencies]
+            a = "1.0.0"
+            b = { version = "1.0.0", optional = true }
+
+            [target.'cfg(target_family = "unix")'.dependencies]
+            b = { optional = true, version = "1.0.0" }
+            c = "1.0.0"
+        "#;
+        const TARGET: &str = r#"
+            [target.'cfg(unix)'.dependencies]
+            a = "1.0.0"
+            b = { version = "1.0.0", optional = true }
+            c = "1.0.0"
+        "#;
+        let mut settings = Settings::default();
+        settings.targets.merge = true;
+        assert_eq!(verify(SOURCE, TARGET, &settings)?, Vec::new());
+        let source = SOURCE.replace("c = \"1.0.0\"", "a = \"2.0.0\"");
+        assert_eq!(
+            verify(&source, TARGET, &settings)?,
+            vec![
+                Difference::Conflict(
+                    "target.\"cfg(target_family = \\\"unix\\\")\".dependencies.a".to_string()
+                ),
+                Difference::Added("target.\"cfg(unix)\".dependencies.c".to_string()),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sorted() -> Result<()> {
+        const SOURCE: &str = r#"
+            [package]
+            keywords = ["b", "a"]
+
+            [features]
+            std = ["serde/std", "alloc"]
+        "#;
+        const TARGET: &str = r#"
+            [package]
+            keywords = ["a", "b"]
+
+            [features]
+            std = ["alloc", "serde/std"]
+        "#;
+        let mut settings = Settings::default();
+        settings.package.keywords.order = Order::Ordered(Ordered::Alphabetic);
+        settings.features.feature.order = Order::Ordered(Ordered::Alphabetic);
+        assert_eq!(verify(SOURCE, TARGET, &settings)?, Vec::new());
+        settings.package.keywords.order = Order::Unordered;
+        assert_eq!(
+            verify(SOURCE, TARGET, &settings)?,
+            vec![
+                Difference::Changed("package.keywords.[0]".to_string()),
+                Difference::Changed("package.keywords.[1]".to_string()),
+            ],
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn different() -> Result<()> {
+        const SOURCE: &str = r#"
+            [dependencies]
+            a = { version = "1.0.0", features = ["b", "c"] }
+            "d.e" = "1.0.0"
+        "#;
+        const TARGET: &str = r#"
+            [dependencies]
+            a = { version = "1.0.0", features = ["c", "b"], optional = true }
+        "#;
+        assert_eq!(
+            verify(SOURCE, TARGET, &Settings::default())?,
+            vec![
+                Difference::Changed("dependencies.a.features.[0]".to_string()),
+                Difference::Changed("dependencies.a.features.[1]".to_string()),
+                Difference::Added("dependencies.a.optional".to_string()),
+                Difference::Removed("dependencies.\"d.e\"".to_string()),
+            ],
+        );
+        Ok(())
+    }
+}
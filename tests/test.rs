@@ -1,11 +1,69 @@
 use anyhow::Result;
-use cargo_pretty::{Format, Settings};
+use cargo_pretty::{merge_targets, Diagnostic, Format, Group, Inherited, Inline, Layout, Settings};
 use toml_lalrpop::TomlParser;
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    mod arrays {
+        use super::*;
+
+        const SOURCE: &str = r#"
+            [package]
+            keywords = [
+                "b",
+                "a",
+            ]
+        "#;
+
+        const HORIZONTAL: &str = "\n\
+            [package]\n\
+            keywords = [\"a\", \"b\"]\n\
+        ";
+
+        const VERTICAL: &str = "\n\
+            [package]\n\
+            keywords = [\n    \
+                \"a\",\n    \
+                \"b\",\n\
+            ]\n\
+        ";
+
+        fn format(source: &str, layout: Layout, max_width: usize) -> Result<String> {
+            let mut manifest = TomlParser::new().parse(source)?;
+            let mut settings = Settings::default();
+            settings.max_width = max_width;
+            settings.package.keywords.layout = layout;
+            let formated = format!("{}", manifest.format(&settings));
+            // Formatting the formatted manifest changes nothing.
+            let mut manifest = TomlParser::new().parse(&formated)?;
+            assert_eq!(format!("{}", manifest.format(&settings)), formated);
+            Ok(formated)
+        }
+
+        #[test]
+        fn horizontal() -> Result<()> {
+            assert_eq!(format(SOURCE, Layout::Horizontal, 100)?, HORIZONTAL);
+            Ok(())
+        }
+
+        #[test]
+        fn vertical() -> Result<()> {
+            assert_eq!(format(SOURCE, Layout::Vertical, 100)?, VERTICAL);
+            assert_eq!(format(HORIZONTAL, Layout::Vertical, 100)?, VERTICAL);
+            Ok(())
+        }
+
+        #[test]
+        fn auto() -> Result<()> {
+            assert_eq!(format(SOURCE, Layout::Auto, 100)?, HORIZONTAL);
+            assert_eq!(format(SOURCE, Layout::Auto, 20)?, VERTICAL);
+            assert_eq!(format(HORIZONTAL, Layout::Auto, 20)?, VERTICAL);
+            Ok(())
+        }
+    }
+
     mod badges {
         use super::*;
 
@@ -108,4 +166,718 @@ mod test {
             Ok(())
         }
     }
+
+    mod dependencies {
+        use super::*;
+
+        const SOURCE: &str = r#"
+            [dependencies]
+            short = { version = "1.0.0" }
+            long = { version = "1.0.0", default-features = false, features = ["first", "second", "third", "fourth"] }
+        "#;
+
+        #[test]
+        fn inline_auto() -> Result<()> {
+            const TARGET: &str = "\n\
+                [dependencies]\n\
+                short = { version = \"1.0.0\" }\n\
+                \n\
+                [dependencies.long]\n\
+                version = \"1.0.0\"\n\
+                default-features = false\n\
+                features = [\"first\", \"second\", \"third\", \"fourth\"]\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.dependencies.inline = Inline::Auto;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn inline_auto_max_width() -> Result<()> {
+            const TARGET: &str = "\n\
+                [dependencies]\n\
+                long = { version = \"1.0.0\", default-features = false, features = [\"first\", \"second\", \"third\", \"fourth\"] }\n\
+                short = { version = \"1.0.0\" }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.dependencies.inline = Inline::Auto;
+            settings.max_width = 120;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn inline_auto_section() -> Result<()> {
+            const SOURCE: &str = r#"
+                [dependencies.b]
+                version = "1.0.0"
+
+                [dependencies.a]
+                version = "1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [dependencies]\n\
+                a = { version = \"1.0.0\" }\n\
+                b = { version = \"1.0.0\" }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.dependencies.inline = Inline::Auto;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn group() -> Result<()> {
+            const SOURCE: &str = r#"
+                [dependencies]
+                a = "1.0.0"
+                b = { path = "../b" }
+                c = { git = "https://github.com/c/c" }
+                d = { workspace = true }
+                e = { path = "../e" }
+
+                [dependencies.f]
+                # pinned because of f
+                path = "../f"
+            "#;
+            const TARGET: &str = "\n\
+                [dependencies]\n\
+                # Workspace dependencies\n\
+                d = { workspace = true }\n\
+                \n\
+                # Path dependencies\n\
+                b = { path = \"../b\" }\n\
+                e = { path = \"../e\" }\n\
+                \n\
+                # Git dependencies\n\
+                c = { git = \"https://github.com/c/c\" }\n\
+                \n\
+                # Registry dependencies\n\
+                a = \"1.0.0\"\n\
+                \n\
+                [dependencies.f]\n\
+                # pinned because of f\n\
+                path = \"../f\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.dependencies.group = Group::SourceWithHeaders;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn target() -> Result<()> {
+            const SOURCE: &str = r#"
+                [target.'cfg(unix)'.dev-dependencies]
+                c = { optional = true, version = "1.0.0" }
+                b = "1.0.0"
+
+                [target.'cfg(unix)'.dependencies]
+                a = { features = ["d"], version = "1.0.0" }
+            "#;
+            const TARGET: &str = "\n\
+                [target.\"cfg(unix)\".dependencies]\n\
+                a = { version = \"1.0.0\", features = [\"d\"] }\n\
+                \n\
+                [target.\"cfg(unix)\".dev-dependencies]\n\
+                b = \"1.0.0\"\n\
+                c = { version = \"1.0.0\", optional = true }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
+
+    mod comments {
+        use super::*;
+
+        #[test]
+        fn sort_table() -> Result<()> {
+            const SOURCE: &str = r#"
+                [dependencies]
+                # pinned because of b
+                b = "=1.0.0" # post comment for b
+                a = "1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [dependencies]\n\
+                a = \"1.0.0\"\n\
+                # pinned because of b\n\
+                b = \"=1.0.0\" # post comment for b\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn sort_sections() -> Result<()> {
+            const SOURCE: &str = r#"
+                # pre comment for dependencies
+                [dependencies]
+                a = "1.0.0"
+
+                # pre comment for package
+                [package] # post comment for package
+                name = "a"
+            "#;
+            const TARGET: &str = "\n\
+                # pre comment for package\n\
+                [package] # post comment for package\n\
+                name = \"a\"\n\
+                \n\
+                # pre comment for dependencies\n\
+                [dependencies]\n\
+                a = \"1.0.0\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn sort_array() -> Result<()> {
+            const SOURCE: &str = r#"
+                [package]
+                keywords = [
+                    # pre comment for b
+                    "b",
+                    "a",
+                ]
+            "#;
+            const TARGET: &str = "\n\
+                [package]\n\
+                keywords = [\n    \
+                    \"a\",\n    \
+                    # pre comment for b\n    \
+                    \"b\",\n\
+                ]\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn inline_blocked() -> Result<()> {
+            const SOURCE: &str = r#"
+                [dependencies.a]
+                # pinned because of a
+                version = "=1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [dependencies.a]\n\
+                # pinned because of a\n\
+                version = \"=1.0.0\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn expand() -> Result<()> {
+            const SOURCE: &str = r#"
+                [dependencies]
+                # pre comment for a
+                a = { version = "1.0.0" } # post comment for a
+            "#;
+            const TARGET: &str = "\n\
+                # pre comment for a\n\
+                [dependencies.a] # post comment for a\n\
+                version = \"1.0.0\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.dependencies.inline = Inline::Manual(None);
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
+
+    mod features {
+        use super::*;
+        use cargo_pretty::{Order, Ordered};
+
+        #[test]
+        fn order_feature() -> Result<()> {
+            const SOURCE: &str = r#"
+                [features]
+                std = ["serde?/std", "dep:serde", "alloc", "serde/derive", "dep:itoa"]
+            "#;
+            const TARGET: &str = "\n\
+                [features]\n\
+                std = [\"alloc\", \"dep:itoa\", \"dep:serde\", \"serde/derive\", \"serde?/std\"]\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.features.feature.order = Order::Ordered(Ordered::Feature);
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn order_topological() -> Result<()> {
+            const SOURCE: &str = r#"
+                [features]
+                alloc = []
+                full = ["std", "derive"]
+                derive = ["dep:serde_derive"]
+                std = ["alloc", "serde/std"]
+                default = ["std"]
+            "#;
+            const TARGET: &str = "\n\
+                [features]\n\
+                default = [\"std\"]\n\
+                alloc = []\n\
+                derive = [\"dep:serde_derive\"]\n\
+                std = [\"alloc\", \"serde/std\"]\n\
+                full = [\"derive\", \"std\"]\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.features.order = Order::Ordered(Ordered::Topological);
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
+
+    mod inherited {
+        use super::*;
+
+        const SOURCE: &str = r#"
+            [package]
+            edition.workspace = true
+            name = "a"
+            version = { workspace = true }
+
+            [dependencies]
+            b = { features = ["c"], workspace = true }
+        "#;
+
+        #[test]
+        fn inline() -> Result<()> {
+            const TARGET: &str = "\n\
+                [package]\n\
+                name = \"a\"\n\
+                version = { workspace = true }\n\
+                edition = { workspace = true }\n\
+                \n\
+                [dependencies]\n\
+                b = { workspace = true, features = [\"c\"] }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn inline_none() -> Result<()> {
+            const TARGET: &str = "\n\
+                [package]\n\
+                name = \"a\"\n\
+                version = { workspace = true }\n\
+                edition = { workspace = true }\n\
+                \n\
+                [dependencies.b]\n\
+                workspace = true\n\
+                features = [\"c\"]\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.dependencies.inline = Inline::Manual(None);
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn dotted() -> Result<()> {
+            const TARGET: &str = "\n\
+                [package]\n\
+                name = \"a\"\n\
+                version.workspace = true\n\
+                edition.workspace = true\n\
+                \n\
+                [dependencies]\n\
+                b = { workspace = true, features = [\"c\"] }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.inherited = Inherited::Dotted;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn not_inheritable() -> Result<()> {
+            const SOURCE: &str = r#"
+                [package]
+                name = "a"
+
+                [package.metadata.a]
+                workspace = true
+
+                [lints]
+                workspace = true
+
+                [workspace.package]
+                version = { workspace = true }
+            "#;
+            const TARGET: &str = "\n\
+                [package]\n\
+                name = \"a\"\n\
+                \n\
+                [package.metadata.a]\n\
+                workspace = true\n\
+                \n\
+                [lints]\n\
+                workspace = true\n\
+                \n\
+                [workspace.package]\n\
+                version = { workspace = true }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.inherited = Inherited::Dotted;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
+
+    mod lints {
+        use super::*;
+        use cargo_pretty::settings::lints::SortBy;
+
+        const SOURCE: &str = r#"
+            [lints.clippy]
+            pedantic = { level = "warn", priority = -1 }
+            module_name_repetitions = "allow"
+
+            [lints.rust]
+            unsafe_code = "forbid"
+            missing_docs = "warn"
+        "#;
+
+        #[test]
+        fn order() -> Result<()> {
+            const TARGET: &str = "\n\
+                [lints.rust]\n\
+                missing_docs = \"warn\"\n\
+                unsafe_code = \"forbid\"\n\
+                \n\
+                [lints.clippy]\n\
+                module_name_repetitions = \"allow\"\n\
+                pedantic = { level = \"warn\", priority = -1 }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn sort_by_level() -> Result<()> {
+            const TARGET: &str = "\n\
+                [lints.rust]\n\
+                unsafe_code = \"forbid\"\n\
+                missing_docs = \"warn\"\n\
+                \n\
+                [lints.clippy]\n\
+                pedantic = { level = \"warn\", priority = -1 }\n\
+                module_name_repetitions = \"allow\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.lints.tool.sort_by = SortBy::Level;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn sort_by_priority() -> Result<()> {
+            const TARGET: &str = "\n\
+                [lints.rust]\n\
+                missing_docs = \"warn\"\n\
+                unsafe_code = \"forbid\"\n\
+                \n\
+                [lints.clippy]\n\
+                pedantic = { level = \"warn\", priority = -1 }\n\
+                module_name_repetitions = \"allow\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.lints.tool.sort_by = SortBy::Priority;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
+
+    mod package {
+        use super::*;
+
+        // Every key of the `[package]` section in the order of the reference
+        // (https://doc.rust-lang.org/cargo/reference/manifest.html), but the
+        // `metadata` table. A key missing from the package order would sort
+        // alphabetically after all the enumerated keys.
+        const TARGET: &str = "\n\
+            [package]\n\
+            name = \"a\"\n\
+            version = \"1.0.0\"\n\
+            authors = [\"a\"]\n\
+            edition = \"2021\"\n\
+            rust-version = \"1.56\"\n\
+            description = \"A\"\n\
+            documentation = \"https://docs.rs/a\"\n\
+            readme = \"README.md\"\n\
+            homepage = \"https://a.rs\"\n\
+            repository = \"https://github.com/a/a\"\n\
+            license = \"MIT\"\n\
+            license-file = \"LICENSE\"\n\
+            keywords = [\"a\"]\n\
+            categories = [\"parsing\"]\n\
+            workspace = \"..\"\n\
+            build = \"build.rs\"\n\
+            links = \"a\"\n\
+            exclude = [\"a\"]\n\
+            include = [\"b\"]\n\
+            publish = false\n\
+            default-run = \"a\"\n\
+            autolib = false\n\
+            autobins = false\n\
+            autoexamples = false\n\
+            autotests = false\n\
+            autobenches = false\n\
+            resolver = \"2\"\n\
+        ";
+
+        #[test]
+        fn order() -> Result<()> {
+            const SOURCE: &str = r#"
+                [package]
+                resolver = "2"
+                autobenches = false
+                autotests = false
+                autoexamples = false
+                autobins = false
+                autolib = false
+                default-run = "a"
+                publish = false
+                include = ["b"]
+                exclude = ["a"]
+                links = "a"
+                build = "build.rs"
+                workspace = ".."
+                categories = ["parsing"]
+                keywords = ["a"]
+                license-file = "LICENSE"
+                license = "MIT"
+                repository = "https://github.com/a/a"
+                homepage = "https://a.rs"
+                readme = "README.md"
+                documentation = "https://docs.rs/a"
+                description = "A"
+                rust-version = "1.56"
+                edition = "2021"
+                authors = ["a"]
+                version = "1.0.0"
+                name = "a"
+            "#;
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
+
+    mod targets {
+        use super::*;
+
+        #[test]
+        fn canonicalize() -> Result<()> {
+            const SOURCE: &str = r#"
+                [target.'cfg(all(windows,target_env="msvc"))'.dependencies]
+                b = "1.0.0"
+
+                [target.'cfg( unix )'.dependencies]
+                a = "1.0.0"
+
+                [target.'cfg(not(unix))'.dependencies]
+                d = "1.0.0"
+
+                [target.x86_64-pc-windows-gnu.dependencies]
+                c = "1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [target.x86_64-pc-windows-gnu.dependencies]\n\
+                c = \"1.0.0\"\n\
+                \n\
+                [target.\"cfg(unix)\".dependencies]\n\
+                a = \"1.0.0\"\n\
+                \n\
+                [target.\"cfg(all(target_env = \\\"msvc\\\", windows))\".dependencies]\n\
+                b = \"1.0.0\"\n\
+                \n\
+                [target.\"cfg(not(unix))\".dependencies]\n\
+                d = \"1.0.0\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.targets.canonicalize = true;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn merge() -> Result<()> {
+            const SOURCE: &str = r#"
+                [target.'cfg(unix)'.dependencies]
+                a = "1.0.0"
+                b = { version = "1.0.0", optional = true }
+
+                [target.'cfg(target_family = "unix")'.dependencies]
+                b = { optional = true, version = "1.0.0" }
+                c = "1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [target.\"cfg(unix)\".dependencies]\n\
+                a = \"1.0.0\"\n\
+                b = { version = \"1.0.0\", optional = true }\n\
+                c = \"1.0.0\"\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.targets.merge = true;
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn merge_parsed() -> Result<()> {
+            const SOURCE: &str = r#"
+                [target.'cfg(unix)'.dependencies]
+                a = { version = "1.0.0", default-features = false }
+
+                [target."cfg( unix )".dependencies]
+                a = { default-features = false, version = '1.0.0' }
+            "#;
+            const TARGET: &str = "\n\
+                [target.\"cfg(unix)\".dependencies]\n\
+                a = { version = \"1.0.0\", default-features = false }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.targets.merge = true;
+            assert_eq!(merge_targets(&mut manifest, &settings), Ok(()));
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn merge_conflict() -> Result<()> {
+            const SOURCE: &str = r#"
+                [target.'cfg(unix)'.dependencies]
+                a = "1.0.0"
+
+                [target."cfg( unix )".dependencies]
+                a = "2.0.0"
+            "#;
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let mut settings = Settings::default();
+            settings.targets.merge = true;
+            assert_eq!(
+                merge_targets(&mut manifest, &settings),
+                Err(
+                    Diagnostic::error("target `cfg( unix )` has conflicting specs")
+                        .path("target.\"cfg( unix )\".dependencies.a")
+                        .note("dependency `a` is specified differently")
+                ),
+            );
+            // The manifest is left as is.
+            let mut source = TomlParser::new().parse(SOURCE)?;
+            assert_eq!(
+                format!("{}", manifest.format(&settings)),
+                format!("{}", source.format(&settings)),
+            );
+            Ok(())
+        }
+    }
+
+    mod workspace {
+        use super::*;
+
+        #[test]
+        fn dependencies() -> Result<()> {
+            const SOURCE: &str = r#"
+                [workspace.dependencies]
+                b = { features = ["c"], version = "1.0.0" }
+                a = "1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [workspace.dependencies]\n\
+                a = \"1.0.0\"\n\
+                b = { version = \"1.0.0\", features = [\"c\"] }\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+
+        #[test]
+        fn package() -> Result<()> {
+            const SOURCE: &str = r#"
+                [workspace.package]
+                license = "MIT"
+                keywords = ["b", "a"]
+                version = "1.0.0"
+            "#;
+            const TARGET: &str = "\n\
+                [workspace.package]\n\
+                version = \"1.0.0\"\n\
+                license = \"MIT\"\n\
+                keywords = [\"a\", \"b\"]\n\
+            ";
+            let mut manifest = TomlParser::new().parse(SOURCE)?;
+            let settings = Settings::default();
+            let formated = format!("{}", manifest.format(&settings));
+            assert_eq!(formated, TARGET);
+            Ok(())
+        }
+    }
 }